@@ -0,0 +1,236 @@
+// What a dependency value in package.json points at, after npm-package-arg
+// https://docs.npmjs.com/files/package.json#dependencies
+//
+//   "^1.2.3"                          Range
+//   "latest"                          Tag
+//   "npm:other-name@^2"               Alias
+//   "github:user/repo#semver:^1.0"    Git
+//   "git+ssh://git@host/repo.git#abc123f"
+//   "https://example.com/pkg.tgz"     Tarball
+//   "file:../local", "../local"       File
+//   "link:../local"                   Link (yarn)
+//   "workspace:*"                     Workspace (yarn, pnpm)
+use std::error::Error;
+use std::fmt;
+
+use super::hosted_git::is_shorthand;
+use crate::version::npm_semver::parse_range;
+use crate::version::{Version, VersionQuery};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NpmDepSpec {
+    Range(VersionQuery),
+    Tag(String),
+    Alias { name: String, spec: Box<NpmDepSpec> }, // spec is a Range or a Tag
+    Git { url: String, committish: Option<GitRef> },
+    Tarball(String),
+    File(String),
+    Link(String),
+    Workspace(String), // "*", "^", "~" or a range
+}
+
+// What follows the "#" of a git dependency
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitRef {
+    Commit(String),        // a full SHA-1 or SHA-256 object name
+    Semver(VersionQuery),  // "#semver:^1.0", the highest matching tag
+    Named(String),         // a branch or tag, which may move, or an abbreviated commit
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidDepSpec(pub String);
+
+impl fmt::Display for InvalidDepSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid dependency specifier \"{}\"", self.0)
+    }
+}
+
+impl Error for InvalidDepSpec {}
+
+const HOSTED_PREFIXES: [&str; 4] = ["github:", "gitlab:", "bitbucket:", "gist:"];
+const GIT_PREFIXES: [&str; 6] = ["git:", "git+ssh:", "git+https:", "git+http:", "git+file:", "ssh:"];
+
+impl NpmDepSpec {
+    pub fn parse(spec: &str) -> Result<NpmDepSpec, InvalidDepSpec> {
+        let spec = spec.trim();
+        let invalid = || InvalidDepSpec(spec.to_string());
+        if let Some(rest) = spec.strip_prefix("npm:") {
+            // the name may be scoped: "npm:@scope/name@^2"
+            let at = rest.char_indices().skip(1).find(|(_, c)| *c == '@').map(|(i, _)| i);
+            let (name, spec) = match at {
+                Some(i) => (&rest[..i], NpmDepSpec::parse(&rest[i + 1..])?),
+                None => (rest, NpmDepSpec::Tag("latest".to_string())),
+            };
+            return match spec {
+                NpmDepSpec::Range(_) | NpmDepSpec::Tag(_) if !name.is_empty() => {
+                    Ok(NpmDepSpec::Alias { name: name.to_string(), spec: Box::new(spec) })
+                }
+                _ => Err(invalid()),
+            };
+        }
+        if let Some(rest) = spec.strip_prefix("file:") {
+            return Ok(NpmDepSpec::File(rest.to_string()));
+        }
+        if let Some(rest) = spec.strip_prefix("link:") {
+            return Ok(NpmDepSpec::Link(rest.to_string()));
+        }
+        if let Some(rest) = spec.strip_prefix("workspace:") {
+            return Ok(NpmDepSpec::Workspace(rest.to_string()));
+        }
+        let (url, committish) = match spec.find('#') {
+            Some(i) => (&spec[..i], Some(&spec[i + 1..])),
+            None => (spec, None),
+        };
+        let is_git = HOSTED_PREFIXES.iter().chain(GIT_PREFIXES.iter()).any(|p| url.starts_with(p))
+            || is_scp_like(url)
+            || (is_http(url) && url.ends_with(".git"))
+            || is_shorthand(url);
+        if is_git {
+            let committish = committish.map(parse_committish).transpose().map_err(|_| invalid())?;
+            return Ok(NpmDepSpec::Git { url: url.to_string(), committish });
+        }
+        if is_http(spec) {
+            return Ok(NpmDepSpec::Tarball(spec.to_string()));
+        }
+        if ["./", "../", "/", "~/"].iter().any(|p| spec.starts_with(p))
+            || [".tgz", ".tar.gz", ".tar"].iter().any(|s| spec.ends_with(s))
+        {
+            return Ok(NpmDepSpec::File(spec.to_string()));
+        }
+        if let Ok(query) = parse_range(spec) {
+            return Ok(NpmDepSpec::Range(query));
+        }
+        // npm takes anything that needs no escaping in a URL as a tag
+        let tag_char = |c: char| c.is_ascii_alphanumeric() || "-_.!~*'()".contains(c);
+        if spec.chars().all(tag_char) {
+            return Ok(NpmDepSpec::Tag(spec.to_string()));
+        }
+        Err(invalid())
+    }
+
+    // Whether the spec names one immutable thing: an exact registry version
+    // or a git commit. Tags, branches, ranges and URLs can all move.
+    pub fn is_pinned(&self) -> bool {
+        match self {
+            NpmDepSpec::Range(VersionQuery::Version(v)) | NpmDepSpec::Range(VersionQuery::Eq(v)) => {
+                // "1.2" is 1.2.x
+                matches!(v, Version::SemVer(v) if v.is_complete())
+            }
+            NpmDepSpec::Alias { spec, .. } => spec.is_pinned(),
+            NpmDepSpec::Git { committish: Some(GitRef::Commit(_)), .. } => true,
+            _ => false,
+        }
+    }
+
+    // The registry range, through an alias too; None for tags and the rest
+    pub fn range(&self) -> Option<&VersionQuery> {
+        match self {
+            NpmDepSpec::Range(q) => Some(q),
+            NpmDepSpec::Alias { spec, .. } => spec.range(),
+            _ => None,
+        }
+    }
+
+    // from the registry, as opposed to git, a URL or the local disk
+    pub fn is_registry(&self) -> bool {
+        matches!(self, NpmDepSpec::Range(_) | NpmDepSpec::Tag(_) | NpmDepSpec::Alias { .. })
+    }
+}
+
+fn parse_committish(committish: &str) -> Result<GitRef, ()> {
+    if let Some(range) = committish.strip_prefix("semver:") {
+        return parse_range(range).map(GitRef::Semver).map_err(|_| ());
+    }
+    // "deadbeef" may as well be a branch, only a full object name is a commit
+    let hex = committish.bytes().all(|c| c.is_ascii_hexdigit());
+    if hex && (committish.len() == 40 || committish.len() == 64) {
+        Ok(GitRef::Commit(committish.to_string()))
+    } else if committish.is_empty() {
+        Err(())
+    } else {
+        Ok(GitRef::Named(committish.to_string()))
+    }
+}
+
+fn is_http(s: &str) -> bool {
+    s.starts_with("http://") || s.starts_with("https://")
+}
+
+// "git@github.com:user/repo.git"
+fn is_scp_like(url: &str) -> bool {
+    match (url.find('@'), url.find(':')) {
+        (Some(at), Some(colon)) => at > 0 && at < colon && !url.contains("://"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(spec: &str) -> NpmDepSpec {
+        NpmDepSpec::parse(spec).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn git(url: &str, committish: Option<GitRef>) -> NpmDepSpec {
+        NpmDepSpec::Git { url: url.to_string(), committish }
+    }
+
+    #[test]
+    fn test_parse_dep_spec() {
+        assert_eq!(parse("^1.2.3"), NpmDepSpec::Range(parse_range("^1.2.3").unwrap()));
+        assert_eq!(parse(""), NpmDepSpec::Range(parse_range("*").unwrap()));
+        assert_eq!(parse("latest"), NpmDepSpec::Tag("latest".to_string()));
+        assert_eq!(parse("npm:other-name@^2"), NpmDepSpec::Alias {
+            name: "other-name".to_string(),
+            spec: Box::new(parse("^2")),
+        });
+        assert_eq!(parse("npm:@scope/name@next"), NpmDepSpec::Alias {
+            name: "@scope/name".to_string(),
+            spec: Box::new(NpmDepSpec::Tag("next".to_string())),
+        });
+        assert_eq!(parse("github:user/repo#semver:^1.0"),
+                   git("github:user/repo", Some(GitRef::Semver(parse_range("^1.0").unwrap()))));
+        assert_eq!(parse("git+ssh://git@example.com/repo.git#0a1b2c3d"),
+                   git("git+ssh://git@example.com/repo.git", Some(GitRef::Named("0a1b2c3d".to_string()))));
+        assert_eq!(parse("git@github.com:user/repo.git"), git("git@github.com:user/repo.git", None));
+        assert_eq!(parse("https://github.com/user/repo.git#main"),
+                   git("https://github.com/user/repo.git", Some(GitRef::Named("main".to_string()))));
+        assert_eq!(parse("expressjs/express#4.x"), git("expressjs/express", Some(GitRef::Named("4.x".to_string()))));
+        assert_eq!(parse("https://example.com/pkg.tgz"), NpmDepSpec::Tarball("https://example.com/pkg.tgz".to_string()));
+        assert_eq!(parse("file:../local"), NpmDepSpec::File("../local".to_string()));
+        assert_eq!(parse("../local"), NpmDepSpec::File("../local".to_string()));
+        assert_eq!(parse("pkg-1.0.0.tgz"), NpmDepSpec::File("pkg-1.0.0.tgz".to_string()));
+        assert_eq!(parse("link:../local"), NpmDepSpec::Link("../local".to_string()));
+        assert_eq!(parse("workspace:*"), NpmDepSpec::Workspace("*".to_string()));
+    }
+
+    #[test]
+    fn test_parse_dep_spec_invalid() {
+        assert!(NpmDepSpec::parse("not a tag").is_err());
+        assert!(NpmDepSpec::parse("npm:foo@file:../x").is_err());
+        assert!(NpmDepSpec::parse("github:user/repo#semver:^1.2.3 foo").is_err());
+        assert_eq!(NpmDepSpec::parse("a b").unwrap_err().to_string(), "invalid dependency specifier \"a b\"");
+    }
+
+    #[test]
+    fn test_is_pinned() {
+        assert!(parse("1.2.3").is_pinned());
+        assert!(parse("=1.2.3").is_pinned());
+        assert!(parse("npm:other@1.2.3").is_pinned());
+        assert!(parse("github:user/repo#0a1b2c3d4e5f60718293a4b5c6d7e8f901234567").is_pinned());
+        assert!(!parse("1.2").is_pinned());
+        assert!(!parse("^1.2.3").is_pinned());
+        assert!(!parse("latest").is_pinned());
+        assert!(!parse("github:user/repo#main").is_pinned());
+        assert!(!parse("github:user/repo#deadbeef").is_pinned());
+        assert!(!parse("github:user/repo#0a1b2c3d4e5f60718293a4b5c6d7e8f90123456").is_pinned());
+        assert!(!parse("github:user/repo").is_pinned());
+        assert!(!parse("https://example.com/pkg.tgz").is_pinned());
+        assert!(parse("latest").is_registry());
+        assert_eq!(parse("npm:other@^2").range(), Some(&parse_range("^2").unwrap()));
+        assert_eq!(parse("github:user/repo#semver:^1.0").range(), None);
+        assert!(!parse("file:../local").is_registry());
+    }
+}
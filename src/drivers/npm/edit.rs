@@ -0,0 +1,363 @@
+// Edits to a package.json that touch nothing but the entries edited: the
+// indentation, key order, line endings, trailing newline and every field
+// PackageJson does not model are kept as they are in the file.
+//
+//   let mut editor = PackageJsonEditor::new(text)?;
+//   editor.set_dependency("dependencies", "express", "^4.17.1")?;
+//   editor.move_dependency("mocha", "dependencies", "devDependencies")?;
+//   fs::write(path, editor.as_str())?;
+//
+// Edits work on the text, with the spans of object members found by a small
+// scanner; serde_json only checks the input is JSON to begin with.
+use std::error::Error;
+use std::fmt;
+
+use serde_json::Value;
+
+#[derive(Debug)]
+pub enum EditError {
+    NotJson(serde_json::Error),
+    NotAnObject(String), // the key whose value should have been an object
+    NoSuchKey(String),   // "devDependencies/mocha"
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EditError::NotJson(e) => write!(f, "not JSON: {}", e),
+            EditError::NotAnObject(key) => write!(f, "`{}` is not an object", key),
+            EditError::NoSuchKey(key) => write!(f, "no `{}`", key),
+        }
+    }
+}
+
+impl Error for EditError {}
+
+pub struct PackageJsonEditor {
+    text: String,
+}
+
+// An object in the text, from its "{" to just past its "}"
+struct Object {
+    start: usize,
+    end: usize,
+    members: Vec<Member>,
+}
+
+struct Member {
+    key: String,
+    key_start: usize,
+    key_end: usize,
+    value_start: usize,
+    value_end: usize,
+}
+
+impl PackageJsonEditor {
+    pub fn new(text: impl Into<String>) -> Result<PackageJsonEditor, EditError> {
+        let text = text.into();
+        match serde_json::from_str::<Value>(&text) {
+            Ok(Value::Object(_)) => Ok(PackageJsonEditor { text }),
+            Ok(_) => Err(EditError::NotAnObject("/".to_string())),
+            Err(e) => Err(EditError::NotJson(e)),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+
+    // Sets the spec of name in group, adding the group when it is missing.
+    // A new name goes in alphabetical order if the group is sorted, as npm
+    // keeps it, at the end otherwise.
+    pub fn set_dependency(&mut self, group: &str, name: &str, spec: &str) -> Result<(), EditError> {
+        self.ensure_object(group)?;
+        self.set(&[group], name, &serde_json::to_string(spec).unwrap(), true)
+    }
+
+    // Whether name was in group
+    pub fn remove_dependency(&mut self, group: &str, name: &str) -> Result<bool, EditError> {
+        match self.object(&[group]) {
+            Ok(_) => self.remove(&[group], name),
+            Err(EditError::NoSuchKey(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    // e.g. from dependencies to devDependencies, keeping the spec
+    pub fn move_dependency(&mut self, name: &str, from: &str, to: &str) -> Result<(), EditError> {
+        let object = self.object(&[from])?;
+        let member = object.members.iter().find(|m| m.key == name)
+            .ok_or_else(|| EditError::NoSuchKey(format!("{}/{}", from, name)))?;
+        let value = self.text[member.value_start..member.value_end].to_string();
+        self.remove(&[from], name)?;
+        self.ensure_object(to)?;
+        self.set(&[to], name, &value, true)
+    }
+
+    pub fn set_script(&mut self, name: &str, command: &str) -> Result<(), EditError> {
+        self.ensure_object("scripts")?;
+        self.set(&["scripts"], name, &serde_json::to_string(command).unwrap(), false)
+    }
+
+    // adds {} under a top-level key that is missing
+    fn ensure_object(&mut self, key: &str) -> Result<(), EditError> {
+        match self.object(&[key]) {
+            Err(EditError::NoSuchKey(_)) => self.set(&[], key, "{}", false),
+            result => result.map(|_| ()),
+        }
+    }
+
+    // Sets key in the object at path to value, which is JSON text
+    fn set(&mut self, path: &[&str], key: &str, value: &str, sorted: bool) -> Result<(), EditError> {
+        let object = self.object(path)?;
+        if let Some(m) = object.members.iter().find(|m| m.key == key) {
+            self.text.replace_range(m.value_start..m.value_end, value);
+            return Ok(());
+        }
+        let quoted = serde_json::to_string(key).unwrap();
+        let members = &object.members;
+        let last = match members.last() {
+            Some(last) => last,
+            None if !self.text.contains('\n') => {
+                // a manifest on one line stays on one line, with the parent's ": " style
+                let parent = match path.split_last() {
+                    Some((_, parent)) => self.object(parent)?.members.first()
+                        .map(|m| self.text[m.key_end..m.value_start].to_string()),
+                    None => None,
+                };
+                let inner = format!("{}{}{}", quoted, parent.as_deref().unwrap_or(":"), value);
+                self.text.replace_range(object.start + 1..object.end - 1, &inner);
+                return Ok(());
+            }
+            None => {
+                // "{}" => "{\n<indent>  "key": value\n<indent>}"
+                let (newline, indent) = (self.newline(), self.line_indent(object.start));
+                let inner = format!("{}{}{}{}: {}{}{}", newline, indent, self.indent_unit(), quoted, value, newline, indent);
+                self.text.replace_range(object.start + 1..object.end - 1, &inner);
+                return Ok(());
+            }
+        };
+        // the text between members, ": " style and indentation, copied from the last
+        let separator = self.text[last.key_end..last.value_start].to_string();
+        let gap = |i: usize| match i {
+            0 => self.text[object.start + 1..members[0].key_start].to_string(),
+            i => {
+                let prev = &members[i - 1];
+                let between = &self.text[prev.value_end..members[i].key_start];
+                between[between.find(',').map_or(0, |c| c + 1)..].to_string()
+            }
+        };
+        let is_sorted = members.windows(2).all(|w| w[0].key <= w[1].key);
+        let before = members.iter().position(|m| sorted && is_sorted && m.key.as_str() > key);
+        let (at, inserted) = match before {
+            Some(i) => (members[i].key_start, format!("{}{}{},{}", quoted, separator, value, gap(i))),
+            None => (last.value_end, format!(",{}{}{}{}", gap(members.len() - 1), quoted, separator, value)),
+        };
+        self.text.insert_str(at, &inserted);
+        Ok(())
+    }
+
+    // Whether key was there
+    fn remove(&mut self, path: &[&str], key: &str) -> Result<bool, EditError> {
+        let object = self.object(path)?;
+        let i = match object.members.iter().position(|m| m.key == key) {
+            Some(i) => i,
+            None => return Ok(false),
+        };
+        let members = &object.members;
+        let range = if members.len() == 1 {
+            object.start + 1..object.end - 1 // leaves "{}"
+        } else if i + 1 < members.len() {
+            members[i].key_start..members[i + 1].key_start
+        } else {
+            members[i - 1].value_end..members[i].value_end
+        };
+        self.text.replace_range(range, "");
+        Ok(true)
+    }
+
+    fn object(&self, path: &[&str]) -> Result<Object, EditError> {
+        let mut object = parse_object(&self.text, skip_ws(self.text.as_bytes(), 0));
+        for (n, key) in path.iter().enumerate() {
+            let member = object.members.iter().find(|m| m.key == *key)
+                .ok_or_else(|| EditError::NoSuchKey(path[..=n].join("/")))?;
+            if self.text.as_bytes()[member.value_start] != b'{' {
+                return Err(EditError::NotAnObject(path[..=n].join("/")));
+            }
+            object = parse_object(&self.text, member.value_start);
+        }
+        Ok(object)
+    }
+
+    fn newline(&self) -> &'static str {
+        if self.text.contains("\r\n") { "\r\n" } else { "\n" }
+    }
+
+    // the whitespace the line containing position starts with
+    fn line_indent(&self, position: usize) -> &str {
+        let line_start = self.text[..position].rfind('\n').map_or(0, |i| i + 1);
+        let line = &self.text[line_start..];
+        &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+    }
+
+    // one level of indentation, as the file's top-level keys have it
+    fn indent_unit(&self) -> String {
+        let root = parse_object(&self.text, skip_ws(self.text.as_bytes(), 0));
+        match root.members.first() {
+            Some(m) if self.text[root.start..m.key_start].contains('\n') => self.line_indent(m.key_start).to_string(),
+            _ => "  ".to_string(),
+        }
+    }
+}
+
+// The text must be JSON, and start is at a "{"
+fn parse_object(text: &str, start: usize) -> Object {
+    let b = text.as_bytes();
+    let mut members = vec![];
+    let mut i = start + 1;
+    loop {
+        i = skip_ws(b, i);
+        match b[i] {
+            b'}' => return Object { start, end: i + 1, members },
+            b',' => i += 1,
+            _ => {
+                let key_start = i;
+                let key_end = skip_string(b, i);
+                let key = serde_json::from_str(&text[key_start..key_end]).unwrap();
+                let value_start = skip_ws(b, skip_ws(b, key_end) + 1); // past the ":"
+                let value_end = skip_value(b, value_start);
+                members.push(Member { key, key_start, key_end, value_start, value_end });
+                i = value_end;
+            }
+        }
+    }
+}
+
+fn skip_ws(b: &[u8], mut i: usize) -> usize {
+    while i < b.len() && b[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+// from the opening quote to just past the closing one
+fn skip_string(b: &[u8], mut i: usize) -> usize {
+    i += 1;
+    while b[i] != b'"' {
+        i += if b[i] == b'\\' { 2 } else { 1 };
+    }
+    i + 1
+}
+
+fn skip_value(b: &[u8], mut i: usize) -> usize {
+    match b[i] {
+        b'"' => skip_string(b, i),
+        b'{' | b'[' => {
+            let mut depth = 0;
+            loop {
+                match b[i] {
+                    b'"' => {
+                        i = skip_string(b, i);
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => depth -= 1,
+                    _ => {}
+                }
+                i += 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+        }
+        _ => {
+            while i < b.len() && !b",}] \t\r\n".contains(&b[i]) {
+                i += 1;
+            }
+            i
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = "{\n    \"name\": \"pkg\",\n    \"x-custom\": {\"keep\": [1, {\"}\": \"]\"}]},\n    \"dependencies\": {\n        \"a\": \"^1.0.0\",\n        \"c\": \"~2.0.0\"\n    },\n    \"devDependencies\": {}\n}\n";
+
+    fn edit(f: impl FnOnce(&mut PackageJsonEditor) -> Result<(), EditError>) -> String {
+        let mut editor = PackageJsonEditor::new(MANIFEST).unwrap();
+        f(&mut editor).unwrap();
+        serde_json::from_str::<Value>(editor.as_str()).unwrap();
+        editor.into_string()
+    }
+
+    #[test]
+    fn test_set_dependency() {
+        assert_eq!(edit(|e| e.set_dependency("dependencies", "a", "^1.2.0")),
+                   MANIFEST.replace("\"^1.0.0\"", "\"^1.2.0\""));
+        assert_eq!(edit(|e| e.set_dependency("dependencies", "b", "3")),
+                   MANIFEST.replace("\"c\"", "\"b\": \"3\",\n        \"c\""));
+        assert_eq!(edit(|e| e.set_dependency("dependencies", "d", "4")),
+                   MANIFEST.replace("\"~2.0.0\"", "\"~2.0.0\",\n        \"d\": \"4\""));
+        assert_eq!(edit(|e| e.set_dependency("devDependencies", "mocha", "^8")),
+                   MANIFEST.replace("{}", "{\n        \"mocha\": \"^8\"\n    }"));
+        assert_eq!(edit(|e| e.set_dependency("peerDependencies", "react", "*")),
+                   MANIFEST.replace("{}\n}", "{},\n    \"peerDependencies\": {\n        \"react\": \"*\"\n    }\n}"));
+
+        // compact files stay compact, whatever their ": " style
+        for (json, expected) in [
+            ("{\"name\":\"x\",\"dependencies\":{}}", "{\"name\":\"x\",\"dependencies\":{\"a\":\"1\"}}"),
+            ("{\"name\": \"x\", \"dependencies\": {}}", "{\"name\": \"x\", \"dependencies\": {\"a\": \"1\"}}"),
+            ("{\"name\":\"x\"}", "{\"name\":\"x\",\"dependencies\":{\"a\":\"1\"}}"),
+            ("{}", "{\"dependencies\":{\"a\":\"1\"}}"),
+        ].iter() {
+            let mut editor = PackageJsonEditor::new(*json).unwrap();
+            editor.set_dependency("dependencies", "a", "1").unwrap();
+            assert_eq!(editor.as_str(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_remove_and_move_dependency() {
+        assert_eq!(edit(|e| e.remove_dependency("dependencies", "a").map(|r| assert!(r))),
+                   MANIFEST.replace("\"a\": \"^1.0.0\",\n        ", ""));
+        assert_eq!(edit(|e| e.remove_dependency("dependencies", "c").map(|r| assert!(r))),
+                   MANIFEST.replace(",\n        \"c\": \"~2.0.0\"", ""));
+        assert_eq!(edit(|e| e.remove_dependency("optionalDependencies", "c").map(|r| assert!(!r))), MANIFEST);
+        assert_eq!(edit(|e| e.move_dependency("c", "dependencies", "devDependencies")),
+                   MANIFEST.replace(",\n        \"c\": \"~2.0.0\"", "")
+                       .replace("{}", "{\n        \"c\": \"~2.0.0\"\n    }"));
+        let mut editor = PackageJsonEditor::new(MANIFEST).unwrap();
+        assert_eq!(editor.move_dependency("z", "dependencies", "devDependencies").unwrap_err().to_string(),
+                   "no `dependencies/z`");
+    }
+
+    #[test]
+    fn test_set_script() {
+        let compact = "{\"name\":\"pkg\",\"scripts\":{\"test\":\"mocha\"}}";
+        let mut editor = PackageJsonEditor::new(compact).unwrap();
+        editor.set_script("lint", "eslint \"src/**\"").unwrap();
+        assert_eq!(editor.as_str(), "{\"name\":\"pkg\",\"scripts\":{\"test\":\"mocha\",\"lint\":\"eslint \\\"src/**\\\"\"}}");
+        let crlf = MANIFEST.replace('\n', "\r\n");
+        let mut editor = PackageJsonEditor::new(crlf.clone()).unwrap();
+        editor.set_script("test", "mocha").unwrap();
+        assert!(editor.as_str().ends_with("{},\r\n    \"scripts\": {\r\n        \"test\": \"mocha\"\r\n    }\r\n}\r\n"));
+        let mut editor = PackageJsonEditor::new("{\"scripts\": \"\"}").unwrap();
+        assert_eq!(editor.set_script("test", "mocha").unwrap_err().to_string(), "`scripts` is not an object");
+        assert!(PackageJsonEditor::new("[]").is_err());
+        assert!(PackageJsonEditor::new("{").is_err());
+    }
+
+    #[test]
+    fn test_edit_fixture() {
+        let json = std::fs::read_to_string("src/drivers/npm/fixtures/express-4.17.1-package.json").unwrap();
+        let mut editor = PackageJsonEditor::new(json.clone()).unwrap();
+        editor.set_dependency("dependencies", "qs", "6.7.1").unwrap();
+        assert_eq!(editor.as_str(), json.replace("\"qs\": \"6.7.0\"", "\"qs\": \"6.7.1\""));
+    }
+}
@@ -0,0 +1,302 @@
+// Checks on a package.json beyond what deserializing it enforces, each
+// reported at the JSON pointer of the offending value:
+//
+//   error at /name: name can no longer contain capital letters
+//
+// Name rules are those of validate-npm-package-name, for new packages.
+use std::fmt;
+
+use super::dep_spec::NpmDepSpec;
+use super::{Bin, Funding, Man, PackageJson};
+use crate::version::SemVer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning, // publishes, but probably not as intended
+    Error,   // npm refuses it
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub path: String, // JSON pointer, "/dependencies/@scope~1name"
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{} at {}: {}", severity, self.path, self.message)
+    }
+}
+
+const MAX_NAME_LENGTH: usize = 214;
+const BLACKLISTED_NAMES: [&str; 2] = ["node_modules", "favicon.ico"];
+const NODE_BUILTINS: [&str; 26] = [
+    "assert", "buffer", "child_process", "cluster", "console", "crypto", "dgram", "dns", "events", "fs", "http",
+    "https", "module", "net", "os", "path", "punycode", "querystring", "readline", "stream", "string_decoder",
+    "tls", "tty", "url", "util", "zlib",
+];
+
+struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    fn push(&mut self, severity: Severity, path: &[&str], message: String) {
+        let path = path.iter().map(|p| format!("/{}", p.replace('~', "~0").replace('/', "~1"))).collect();
+        self.0.push(Diagnostic { severity, path, message });
+    }
+
+    fn error(&mut self, path: &[&str], message: String) {
+        self.push(Severity::Error, path, message);
+    }
+
+    fn warning(&mut self, path: &[&str], message: String) {
+        self.push(Severity::Warning, path, message);
+    }
+}
+
+impl PackageJson {
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut d = Diagnostics(vec![]);
+        validate_name(&self.name, &mut d);
+        if let Err(e) = self.version.parse::<SemVer>() {
+            d.error(&["version"], format!("version is not valid semver: {}", e));
+        }
+        if let Some(bugs) = &self.bugs {
+            if bugs.url().is_none() && bugs.email().is_none() {
+                d.error(&["bugs"], "must provide one or both of `bugs.url`, `bugs.email`".to_string());
+            }
+        }
+        self.validate_files(&mut d);
+        self.validate_bin_and_man(&mut d);
+        self.validate_people_and_funding(&mut d);
+
+        let groups = [
+            ("dependencies", &self.dependencies),
+            ("devDependencies", &self.dev_dependencies),
+            ("peerDependencies", &self.peer_dependencies),
+            ("optionalDependencies", &self.optional_dependencies),
+        ];
+        for (key, group) in groups.iter() {
+            for (name, spec) in group.iter().flatten() {
+                if let Err(e) = NpmDepSpec::parse(spec) {
+                    d.error(&[key, name], e.to_string());
+                }
+            }
+        }
+
+        let (key, bundled) = self.bundled().unwrap_or_default();
+        let declared = |name: &String| {
+            [&self.dependencies, &self.optional_dependencies].iter()
+                .any(|g| g.as_ref().is_some_and(|g| g.contains_key(name)))
+        };
+        for (i, name) in bundled.iter().enumerate() {
+            if !declared(name) {
+                // npm packs it if it happens to be installed, e.g. as a dependency's dependency
+                d.warning(&[key, &i.to_string()], format!("bundled `{}` is not in dependencies", name));
+            }
+        }
+
+        if self.private == Some(true) && self.publish_config.is_some() {
+            d.warning(&["publishConfig"], "private packages are never published, publishConfig has no effect".to_string());
+        }
+        d.0
+    }
+
+    fn validate_files(&self, d: &mut Diagnostics) {
+        for (i, glob) in self.files.iter().flatten().enumerate() {
+            let path = ["files", &i.to_string()];
+            let balanced = |open, close| glob.matches(open).count() == glob.matches(close).count();
+            if glob.trim().is_empty() {
+                d.error(&path, "empty pattern".to_string());
+            } else if glob.split('/').any(|s| s == "..") {
+                d.error(&path, format!("`{}` reaches outside the package", glob));
+            } else if !balanced('[', ']') || !balanced('{', '}') {
+                d.error(&path, format!("`{}` is not a valid glob", glob));
+            } else if glob.starts_with('/') {
+                d.warning(&path, format!("`{}` is relative to the package root, not the filesystem", glob));
+            }
+        }
+    }
+
+    fn validate_bin_and_man(&self, d: &mut Diagnostics) {
+        match &self.bin {
+            Some(Bin::Object(bins)) => {
+                for (name, path) in bins {
+                    if name.is_empty() || name.contains('/') || path.trim().is_empty() {
+                        d.error(&["bin", name], format!("`{}` is not a valid command name and path", name));
+                    }
+                }
+            }
+            Some(Bin::String(path)) if path.trim().is_empty() => d.error(&["bin"], "empty path".to_string()),
+            _ => {}
+        }
+        let directories = self.directories.as_ref();
+        if self.bin.is_some() && directories.is_some_and(|dirs| dirs.bin.is_some()) {
+            d.warning(&["directories", "bin"], "`directories.bin` is ignored as `bin` is set".to_string());
+        }
+
+        let pages = match &self.man {
+            Some(Man::String(page)) => vec![(None, page)],
+            Some(Man::Array(pages)) => pages.iter().enumerate().map(|(i, p)| (Some(i.to_string()), p)).collect(),
+            None => vec![],
+        };
+        for (i, page) in pages {
+            // "foo.1" or "foo.1.gz", installed as man 1 foo
+            let page_name = page.strip_suffix(".gz").unwrap_or(page);
+            let section = page_name.rsplit('.').next().filter(|s| *s != page_name);
+            if !section.is_some_and(|s| s.starts_with(|c: char| c.is_ascii_digit())) {
+                let path: Vec<&str> = std::iter::once("man").chain(i.as_deref()).collect();
+                d.warning(&path, format!("`{}` does not end in a section number, like foo.1", page));
+            }
+        }
+
+        let dirs = directories.into_iter().flat_map(|dirs| {
+            [("lib", &dirs.lib), ("bin", &dirs.bin), ("man", &dirs.man), ("doc", &dirs.doc),
+             ("example", &dirs.example), ("test", &dirs.test)]
+        });
+        for (key, dir) in dirs {
+            if let Some(dir) = dir {
+                if dir.starts_with('/') || dir.split('/').any(|s| s == "..") {
+                    d.error(&["directories", key], format!("`{}` is not inside the package", dir));
+                }
+            }
+        }
+    }
+
+    fn validate_people_and_funding(&self, d: &mut Diagnostics) {
+        if self.author.as_ref().is_some_and(|p| p.entry().name.is_none()) {
+            d.warning(&["author"], "person has no name and is left out".to_string());
+        }
+        for (key, people) in [("contributors", &self.contributors), ("maintainers", &self.maintainers)].iter() {
+            for (i, person) in people.iter().flatten().enumerate() {
+                if person.entry().name.is_none() {
+                    d.warning(&[key, &i.to_string()], "person has no name and is left out".to_string());
+                }
+            }
+        }
+        if let Some(funding) = &self.funding {
+            validate_funding(funding, &mut vec!["funding".to_string()], d);
+        }
+    }
+}
+
+fn validate_funding(funding: &Funding, path: &mut Vec<String>, d: &mut Diagnostics) {
+    match funding {
+        Funding::FundingEntry(e) if e.url.is_none() => {
+            let path: Vec<&str> = path.iter().map(String::as_str).collect();
+            d.warning(&path, "funding has no url and is left out".to_string());
+        }
+        Funding::Array(entries) => {
+            for (i, entry) in entries.iter().enumerate() {
+                path.push(i.to_string());
+                validate_funding(entry, path, d);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_name(name: &str, d: &mut Diagnostics) {
+    let path = ["name"];
+    if name.is_empty() {
+        return d.error(&path, "name length must be greater than zero".to_string());
+    }
+    if name.starts_with('.') {
+        d.error(&path, "name cannot start with a period".to_string());
+    }
+    if name.starts_with('_') {
+        d.error(&path, "name cannot start with an underscore".to_string());
+    }
+    if name.trim() != name {
+        d.error(&path, "name cannot contain leading or trailing spaces".to_string());
+    }
+    if BLACKLISTED_NAMES.contains(&name.to_lowercase().as_str()) {
+        d.error(&path, format!("{} is a blacklisted name", name));
+    }
+    if NODE_BUILTINS.contains(&name) {
+        d.warning(&path, format!("{} is a core module name", name));
+    }
+    if name.len() > MAX_NAME_LENGTH {
+        d.error(&path, format!("name can no longer contain more than {} characters", MAX_NAME_LENGTH));
+    }
+    if name.to_lowercase() != name {
+        d.error(&path, "name can no longer contain capital letters".to_string());
+    }
+    if name.contains(['~', '\'', '!', '(', ')', '*']) {
+        d.error(&path, "name can no longer contain special characters (\"~'!()*\")".to_string());
+    }
+    // "@scope/name", each part on its own
+    let parts = match name.strip_prefix('@').and_then(|n| n.split_once('/')) {
+        Some((scope, name)) => vec![scope, name],
+        None => vec![name],
+    };
+    // what encodeURIComponent() leaves alone
+    let url_safe = |c: char| c.is_ascii_alphanumeric() || "-_.!~*'()".contains(c);
+    if parts.iter().any(|p| p.is_empty() || !p.chars().all(url_safe)) {
+        d.error(&path, "name can only contain URL-friendly characters".to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(json: &str) -> Vec<String> {
+        let pkg: PackageJson = serde_json::from_str(json).unwrap();
+        pkg.validate().iter().map(|d| d.to_string()).collect()
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert_eq!(validate(r#"{"name": "@scope/pkg.js", "version": "1.0.0"}"#), Vec::<String>::new());
+        assert_eq!(validate(r#"{"name": "Pkg", "version": "1.0.0"}"#),
+                   ["error at /name: name can no longer contain capital letters"]);
+        assert_eq!(validate(r#"{"name": "_a b", "version": "1.0.0"}"#), [
+            "error at /name: name cannot start with an underscore",
+            "error at /name: name can only contain URL-friendly characters",
+        ]);
+        assert_eq!(validate(r#"{"name": "fs", "version": "1.0.0"}"#), ["warning at /name: fs is a core module name"]);
+        assert_eq!(validate(r#"{"name": "@scope/", "version": "1.0.0"}"#),
+                   ["error at /name: name can only contain URL-friendly characters"]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let diagnostics = validate(r#"{
+            "name": "pkg",
+            "version": "1.0",
+            "files": ["lib", "../secrets", "src/{a,b"],
+            "bin": {"pkg": "./bin/pkg"},
+            "directories": {"bin": "./bin"},
+            "dependencies": {"@scope/dep": "^1.0.0"},
+            "bundleDependencies": ["@scope/dep", "missing"],
+            "private": true,
+            "publishConfig": {"registry": "https://registry.example.com"}
+        }"#);
+        assert_eq!(diagnostics, [
+            "error at /version: version is not valid semver: invalid semver at column 4",
+            "error at /files/1: `../secrets` reaches outside the package",
+            "error at /files/2: `src/{a,b` is not a valid glob",
+            "warning at /directories/bin: `directories.bin` is ignored as `bin` is set",
+            "warning at /bundleDependencies/1: bundled `missing` is not in dependencies",
+            "warning at /publishConfig: private packages are never published, publishConfig has no effect",
+        ]);
+        // any number up to Number.MAX_SAFE_INTEGER is valid
+        assert_eq!(validate(r#"{"name": "caniuse-lite", "version": "1.0.30001157"}"#), Vec::<String>::new());
+        assert_eq!(validate(r#"{"name": "pkg", "version": "1.0.9007199254740992"}"#),
+                   ["error at /version: version is not valid semver: version number too large at column 5"]);
+    }
+
+    #[test]
+    fn test_validate_fixtures() {
+        for (name, json) in crate::drivers::npm::tests::fixtures() {
+            let errors: Vec<_> = validate(&json).into_iter().filter(|d| d.starts_with("error")).collect();
+            assert_eq!(errors, Vec::<String>::new(), "{}", name);
+        }
+    }
+}
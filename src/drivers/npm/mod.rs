@@ -1,22 +1,20 @@
-mod npm_semver;
-
-use crate::pest;              
-#[macro_use]              
-use crate::pest_derive;
-use lazy_static::lazy_static;
+pub mod dep_spec;
+pub mod edit;
+pub mod hosted_git;
+pub mod lint;
+pub mod package_lock;
+pub mod resolved;
+pub mod yarn_lock;
 
 use std::collections::BTreeMap as Map; // BTreeMap is ordered
 
 use serde::{Serialize, Deserialize};
-use serde_json;
-use serde_json::{Value, json};
-use chrono::prelude::*;
-use pest::Parser;
-use pest::iterators::{Pair, Pairs};
-use pest::prec_climber::{Assoc, Operator, PrecClimber};                        
-use regex::Regex;
+use serde_json::Value;
 
-use crate::version::{Version, VersionQuery as VQ, SemVer, SemVerField};
+use crate::version::Version;
+use crate::{Dep, DepGroup, PkgInfo, ScriptGroup};
+use dep_spec::NpmDepSpec;
+use lint::Diagnostic;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -33,11 +31,18 @@ struct PackageJson {
     #[serde(skip_serializing_if = "Option::is_none")]
     repository: Option<Repository>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(flatten)]
     bugs: Option<Bugs>,
     #[serde(skip_serializing_if = "Option::is_none")]
     license: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<Person>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    contributors: Option<Vec<Person>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    maintainers: Option<Vec<Person>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    funding: Option<Funding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     files: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     main: Option<String>, // "main.js"
@@ -46,10 +51,8 @@ struct PackageJson {
     #[serde(skip_serializing_if = "Option::is_none")]
     bin: Option<Bin>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(flatten)]
     man: Option<Man>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(flatten)]
     directories: Option<Directories>,
     #[serde(skip_serializing_if = "Option::is_none")]
     scripts: Option<Map<String, String>>,
@@ -62,9 +65,9 @@ struct PackageJson {
     #[serde(skip_serializing_if = "Option::is_none")]
     peer_dependencies: Option<Map<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    bundled_dependencies: Option<Vec<String>>,
+    bundled_dependencies: Option<BundledDependencies>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    bundle_dependencies: Option<Vec<String>>,
+    bundle_dependencies: Option<BundledDependencies>,
     #[serde(skip_serializing_if = "Option::is_none")]
     optional_dependencies: Option<Map<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -83,15 +86,125 @@ struct PackageJson {
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct Bugs {
+#[serde(untagged)]
+enum Bugs {
+    Url(String),          // "https://github.com/owner/project/issues"
+    BugsEntry(BugsEntry), // {"url": "...", "email": "..."}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BugsEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
     url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     email: Option<String>,
 }
 
 impl Bugs {
-    fn validate(self) {
-        if self.url == None && self.email == None {
-            println!("must provide one or both of `bugs.url`, `bugs.email`");
+    fn url(&self) -> Option<&str> {
+        match self {
+            Bugs::Url(url) => Some(url),
+            Bugs::BugsEntry(e) => e.url.as_deref(),
+        }
+    }
+
+    fn email(&self) -> Option<&str> {
+        match self {
+            Bugs::Url(_) => None,
+            Bugs::BugsEntry(e) => e.email.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum BundledDependencies {
+    All(bool),          // true bundles every dependency
+    Names(Vec<String>), // ["renderized", "super-streams"]
+}
+
+impl PackageJson {
+    // the key it is under, either spelling works, and the names bundled
+    fn bundled(&self) -> Option<(&'static str, Vec<String>)> {
+        let (key, bundled) = match (&self.bundled_dependencies, &self.bundle_dependencies) {
+            (Some(b), _) => ("bundledDependencies", b),
+            (None, Some(b)) => ("bundleDependencies", b),
+            (None, None) => return None,
+        };
+        let names = match bundled {
+            BundledDependencies::All(true) => self.dependencies.iter().flat_map(|d| d.keys().cloned()).collect(),
+            BundledDependencies::All(false) => vec![],
+            BundledDependencies::Names(names) => names.clone(),
+        };
+        Some((key, names))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(untagged)]
+enum Person {
+    String(String), // "Barney Rubble <b@rubble.com> (http://barnyrubble.tumblr.com/)"
+    PersonEntry(PersonEntry),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersonEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>, // required, entries without one are left out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+}
+
+impl Person {
+    // the string shorthand, where email and url are both optional
+    fn entry(&self) -> PersonEntry {
+        let s = match self {
+            Person::String(s) => s,
+            Person::PersonEntry(e) => return e.clone(),
+        };
+        let between = |open: char, close: char| {
+            let start = s.find(open)? + 1;
+            let end = start + s[start..].find(close)?;
+            Some(s[start..end].trim().to_string()).filter(|s| !s.is_empty())
+        };
+        let name_end = s.find(['<', '(']).unwrap_or(s.len());
+        let name = Some(s[..name_end].trim().to_string()).filter(|s| !s.is_empty());
+        PersonEntry { name, email: between('<', '>'), url: between('(', ')') }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(untagged)]
+enum Funding {
+    Url(String),                // "https://opencollective.com/foo"
+    FundingEntry(FundingEntry), // {"type": "patreon", "url": "https://www.patreon.com/foo"}
+    Array(Vec<Funding>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FundingEntry {
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    type_: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>, // required, entries without one are left out
+}
+
+impl Funding {
+    fn links(&self) -> Vec<crate::Funding> {
+        match self {
+            Funding::Url(url) => vec![crate::Funding { type_: None, url: url.clone() }],
+            Funding::FundingEntry(e) => e.url.iter()
+                .map(|url| crate::Funding { type_: e.type_.clone(), url: url.clone() })
+                .collect(),
+            Funding::Array(a) => a.iter().flat_map(Funding::links).collect(),
         }
     }
 }
@@ -133,24 +246,124 @@ struct Directories {
 #[serde(rename_all = "camelCase")]
 #[serde(untagged)]                                                  
 enum Repository {
-    URL(String),
+    Url(String),
     RepositoryEntry(RepositoryEntry),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct RepositoryEntry {
+    #[serde(rename = "type")]
     type_: String,
     url: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     directory: Option<String>,
 }
 
+// The shared model of a package.json, with what validate() finds in it.
+// Specs that do not parse and bundled names that are not declared are
+// reported there rather than carried over.
+pub fn parse_package_json(json: &str) -> Result<(PkgInfo, Vec<Diagnostic>), serde_json::Error> {
+    let pkg: PackageJson = serde_json::from_str(json)?;
+    let diagnostics = pkg.validate();
+    Ok((pkg.into(), diagnostics))
+}
+
+impl From<PackageJson> for PkgInfo {
+    fn from(pkg: PackageJson) -> PkgInfo {
+        let groups = [
+            ("dependencies", &pkg.dependencies),
+            ("devDependencies", &pkg.dev_dependencies),
+            ("peerDependencies", &pkg.peer_dependencies),
+            ("optionalDependencies", &pkg.optional_dependencies),
+        ];
+        let mut dependencies: Vec<DepGroup> = groups.iter()
+            .filter_map(|(name, group)| group.as_ref().map(|g| (name, g)))
+            .map(|(name, group)| DepGroup {
+                name: name.to_string(),
+                deps: group.iter().map(|(dep, spec)| to_dep(dep, spec)).collect(),
+            })
+            .collect();
+        // bundled packages are only named, their spec is where they are declared
+        if let Some((_, bundled)) = pkg.bundled() {
+            let spec = |name: &String| {
+                [&pkg.dependencies, &pkg.optional_dependencies].iter()
+                    .find_map(|g| g.as_ref().and_then(|g| g.get(name)))
+            };
+            dependencies.push(DepGroup {
+                name: "bundledDependencies".to_string(),
+                deps: bundled.iter().filter_map(|name| Some(to_dep(name, spec(name)?))).collect(),
+            });
+        }
+
+        let bugs: Vec<_> = pkg.bugs.iter()
+            .flat_map(|b| {
+                let url = b.url().map(|u| crate::Bugs::Url(u.to_string()));
+                url.into_iter().chain(b.email().map(|e| crate::Bugs::Email(e.to_string())))
+            })
+            .collect();
+        let repository = pkg.repository.map(|r| {
+            let (type_, url, directory) = match r {
+                Repository::Url(url) => ("git".to_string(), url, None),
+                Repository::RepositoryEntry(e) => (e.type_, e.url, e.directory),
+            };
+            let hosted = hosted_git::HostedGit::parse(&url);
+            crate::Repository { type_, url: hosted_git::normalize(&url), directory, hosted }
+        });
+        let roles = [
+            ("author", pkg.author.as_slice()),
+            ("contributor", pkg.contributors.as_deref().unwrap_or_default()),
+            ("maintainer", pkg.maintainers.as_deref().unwrap_or_default()),
+        ];
+        let people: Vec<_> = roles.iter()
+            .flat_map(|(role, people)| people.iter().map(move |p| (role, p.entry())))
+            .filter_map(|(role, p)| {
+                Some(crate::Person { name: p.name?, role: role.to_string(), homepage: p.url, email: p.email })
+            })
+            .collect();
+
+        PkgInfo {
+            version: Version::classify(&pkg.version),
+            name: pkg.name,
+            description: pkg.description,
+            keywords: pkg.keywords,
+            homepage: pkg.homepage,
+            bugs: if bugs.is_empty() { None } else { Some(bugs) },
+            license: pkg.license,
+            people: if people.is_empty() { None } else { Some(people) },
+            funding: pkg.funding.as_ref().map(Funding::links),
+            repository,
+            dependencies,
+            scripts: pkg.scripts.as_ref().map(script_groups),
+        }
+    }
+}
+
+fn to_dep(name: &str, spec: &str) -> Dep {
+    let version = NpmDepSpec::parse(spec).ok().and_then(|s| s.range().cloned());
+    Dep { name: name.to_string(), version, spec: spec.to_string() }
+}
+
+// npm runs pretest and posttest around test, so they are one group
+fn script_groups(scripts: &Map<String, String>) -> Vec<ScriptGroup> {
+    let is_hook = |name: &str| {
+        ["pre", "post"].iter().any(|p| name.strip_prefix(p).is_some_and(|base| scripts.contains_key(base)))
+    };
+    scripts.keys()
+        .filter(|name| !is_hook(name))
+        .map(|name| ScriptGroup {
+            name: name.clone(),
+            scripts: [format!("pre{}", name), name.clone(), format!("post{}", name)].iter()
+                .filter_map(|n| scripts.get(n).cloned())
+                .collect(),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
-    use std::env;
     use std::io::BufReader;
 
     #[test]
@@ -164,9 +377,11 @@ mod tests {
         let reader = BufReader::new(file);
         let package_json: PackageJson = serde_json::from_reader(reader).unwrap();
         assert!(package_json.version == "6.14.5");
+        let (key, bundle_deps) = package_json.bundled().unwrap();
+        assert_eq!((key, bundle_deps.len()), ("bundleDependencies", 123));
         let repo = match package_json.repository.unwrap() {
             Repository::RepositoryEntry(re) => re,
-            Repository::URL(_) => panic!("expected RepositoryEntry, not URL"),
+            Repository::Url(_) => panic!("expected RepositoryEntry, not URL"),
         };
         assert!(repo.type_ == "git");
         assert!(repo.url == "https://github.com/npm/cli");
@@ -178,13 +393,231 @@ mod tests {
         assert_eq!(bin_keys, ["npm", "npx"]);
         let deps = package_json.dependencies.unwrap();
         assert_eq!(deps.get("JSONStream").unwrap(), "^1.3.5");
-        let bundle_deps = package_json.bundle_dependencies.unwrap();
-        assert_eq!(bundle_deps.len(), 123);
         let scripts = package_json.scripts.unwrap();
         assert_eq!(scripts.get("licenses").unwrap(),
                    "licensee --production --errors-only");
     }
 
+    fn read_fixture(name: &str) -> PackageJson {
+        let path = format!("src/drivers/npm/fixtures/{}", name);
+        let file = fs::File::open(path).unwrap();
+        serde_json::from_reader(BufReader::new(file)).unwrap()
+    }
+
+    // every package.json fixture, with its name
+    pub(crate) fn fixtures() -> Vec<(&'static str, String)> {
+        ["npm-6.14.5-package.json", "express-4.17.1-package.json", "browserslist-4.14.7-package.json"].iter()
+            .map(|name| (*name, fs::read_to_string(format!("src/drivers/npm/fixtures/{}", name)).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_engine_ranges() {
+        use crate::version::npm_semver::parse_range;
+        let npm = read_fixture("npm-6.14.5-package.json");
+        let node = parse_range(&npm.engines.unwrap()["node"]).unwrap();
+        let v = |s: &str| crate::version::Version::classify(s);
+        assert!(node.matches(&v("6.2.0")) && node.matches(&v("8.11.3")) && node.matches(&v("10.0.0")));
+        assert!(!node.matches(&v("6.1.0")) && !node.matches(&v("7.10.1")) && !node.matches(&v("9.2.0")));
+        let express = read_fixture("express-4.17.1-package.json");
+        let node = parse_range(&express.engines.unwrap()["node"]).unwrap();
+        assert_eq!(node.to_string(), ">=0.10.0");
+    }
+
+    #[test]
+    fn test_pkg_info() {
+        let npm = PkgInfo::from(read_fixture("npm-6.14.5-package.json"));
+        assert_eq!(npm.version.to_string(), "6.14.5");
+        let groups: Vec<_> = npm.dependencies.iter().map(|g| (g.name.as_str(), g.deps.len())).collect();
+        assert_eq!(groups, [("dependencies", 115), ("devDependencies", 13), ("bundledDependencies", 115)]);
+        let dep = npm.dependencies[0].deps.iter().find(|d| d.name == "JSONStream").unwrap();
+        assert_eq!((dep.spec.as_str(), dep.version.as_ref().unwrap().to_string()), ("^1.3.5", "^1.3.5".to_string()));
+        let repository = npm.repository.unwrap();
+        assert_eq!((repository.type_.as_str(), repository.url.as_str()), ("git", "https://github.com/npm/cli"));
+        let scripts = npm.scripts.unwrap();
+        let test = scripts.iter().find(|g| g.name == "test").unwrap();
+        assert_eq!(test.scripts, ["npm run lint", "npm run test-tap --", "rimraf test/npm_cache*"]);
+        assert!(scripts.iter().any(|g| g.name == "preversion")); // there is no version script
+        assert!(!scripts.iter().any(|g| g.name == "pretest"));
+
+        let express = PkgInfo::from(read_fixture("express-4.17.1-package.json"));
+        let repository = express.repository.unwrap();
+        assert_eq!(repository.url, "https://github.com/expressjs/express");
+        assert_eq!(repository.hosted.unwrap().path(), "expressjs/express");
+        assert_eq!(express.scripts.unwrap().len(), 5);
+
+        // caniuse-lite numbers its patches by date, far above 65535
+        let browserslist = PkgInfo::from(read_fixture("browserslist-4.14.7-package.json"));
+        let dep = browserslist.dependencies[0].deps.iter().find(|d| d.name == "caniuse-lite").unwrap();
+        let range = dep.version.as_ref().unwrap();
+        assert_eq!(range.to_string(), "^1.0.30001157");
+        assert!(range.matches(&Version::classify("1.0.30001200")));
+    }
+
+    #[test]
+    fn test_parse_package_json() {
+        let (pkg, diagnostics) = parse_package_json(r#"{
+            "name": "pkg",
+            "version": "1.0.0",
+            "dependencies": {"a": "^1.0.0", "b": "not a tag"},
+            "optionalDependencies": {"c": "~2.1"},
+            "bundleDependencies": ["a", "c", "d"]
+        }"#).unwrap();
+        let diagnostics: Vec<_> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(diagnostics, [
+            "error at /dependencies/b: invalid dependency specifier \"not a tag\"",
+            "warning at /bundleDependencies/2: bundled `d` is not in dependencies",
+        ]);
+        let bundled = pkg.dependencies.iter().find(|g| g.name == "bundledDependencies").unwrap();
+        let specs: Vec<_> = bundled.deps.iter().map(|d| (d.name.as_str(), d.spec.as_str())).collect();
+        assert_eq!(specs, [("a", "^1.0.0"), ("c", "~2.1")]);
+        assert!(parse_package_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_people() {
+        let entry = |s: &str| Person::String(s.to_string()).entry();
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(entry("Isaac Z. Schlueter <i@izs.me> (http://blog.izs.me)"),
+                   PersonEntry { name: some("Isaac Z. Schlueter"), email: some("i@izs.me"), url: some("http://blog.izs.me") });
+        assert_eq!(entry("Barney Rubble (http://barnyrubble.tumblr.com/)"),
+                   PersonEntry { name: some("Barney Rubble"), email: None, url: some("http://barnyrubble.tumblr.com/") });
+        assert_eq!(entry("Barney Rubble <>"), PersonEntry { name: some("Barney Rubble"), email: None, url: None });
+
+        let express = PkgInfo::from(read_fixture("express-4.17.1-package.json"));
+        let people = express.people.unwrap();
+        assert_eq!((people[0].name.as_str(), people[0].role.as_str()), ("TJ Holowaychuk", "author"));
+        assert_eq!(people[0].email.as_deref(), Some("tj@vision-media.ca"));
+        assert!(people[1..].iter().all(|p| p.role == "contributor"));
+    }
+
+    #[test]
+    fn test_funding() {
+        let links = |json: &str| {
+            let funding: Funding = serde_json::from_str(json).unwrap();
+            funding.links().into_iter().map(|f| (f.type_, f.url)).collect::<Vec<_>>()
+        };
+        assert_eq!(links(r#""https://opencollective.com/foo""#), [(None, "https://opencollective.com/foo".to_string())]);
+        assert_eq!(links(r#"[{"type": "patreon", "url": "https://www.patreon.com/foo"}, "https://foo.dev/sponsor"]"#), [
+            (Some("patreon".to_string()), "https://www.patreon.com/foo".to_string()),
+            (None, "https://foo.dev/sponsor".to_string()),
+        ]);
+        assert_eq!(links(r#"[{"type": "patreon"}, "https://foo.dev/sponsor"]"#),
+                   [(None, "https://foo.dev/sponsor".to_string())]);
+    }
+
+    #[test]
+    fn test_malformed_people_and_funding() {
+        // one bad entry is reported and left out, the rest of the manifest stays
+        let (pkg, diagnostics) = parse_package_json(r#"{
+            "name": "pkg",
+            "version": "1.0.0",
+            "author": {"email": "a@example.com"},
+            "contributors": ["Barney Rubble", {"url": "https://example.com"}],
+            "funding": [{"type": "patreon"}, "https://foo.dev/sponsor"],
+            "license": "MIT"
+        }"#).unwrap();
+        let diagnostics: Vec<_> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(diagnostics, [
+            "warning at /author: person has no name and is left out",
+            "warning at /contributors/1: person has no name and is left out",
+            "warning at /funding/0: funding has no url and is left out",
+        ]);
+        let people: Vec<_> = pkg.people.unwrap().into_iter().map(|p| (p.name, p.role)).collect();
+        assert_eq!(people, [("Barney Rubble".to_string(), "contributor".to_string())]);
+        assert_eq!(pkg.funding.unwrap().len(), 1);
+        assert_eq!(pkg.license.as_deref(), Some("MIT"));
+    }
+
+    // Every key that survives re-serialization holds what the manifest had,
+    // and only keys PackageJson does not model are lost.
+    fn assert_round_trip(json: &str) -> Vec<String> {
+        let original: Value = serde_json::from_str(json).unwrap();
+        let package_json: PackageJson = serde_json::from_value(original.clone()).unwrap();
+        let reserialized = serde_json::to_value(&package_json).unwrap();
+        let (original, reserialized) = (original.as_object().unwrap(), reserialized.as_object().unwrap());
+        for (key, value) in reserialized {
+            assert_eq!(Some(value), original.get(key), "{}", key);
+        }
+        original.keys().filter(|k| !reserialized.contains_key(*k)).cloned().collect()
+    }
+
+    #[test]
+    fn test_fixtures() {
+        // every range parses strictly and reads back the same, and the
+        // manifest survives re-serialization whole
+        use crate::version::npm_semver::parse_range;
+        for (name, json) in fixtures() {
+            let package_json: PackageJson = serde_json::from_str(&json).unwrap();
+            let groups = [
+                &package_json.dependencies,
+                &package_json.dev_dependencies,
+                &package_json.peer_dependencies,
+                &package_json.optional_dependencies,
+                &package_json.engines,
+            ];
+            for (dep, range) in groups.iter().filter_map(|g| g.as_ref()).flat_map(|g| g.iter()) {
+                let vq = parse_range(range)
+                    .unwrap_or_else(|e| panic!("{}: {} {:?}: {}", name, dep, range, e));
+                assert_eq!(parse_range(&vq.to_string()).unwrap(), vq, "{}: {} {:?}", name, dep, range);
+            }
+            let dropped = assert_round_trip(&json);
+            assert!(dropped.is_empty(), "{}: {:?}", name, dropped);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dropped = assert_round_trip(r#"{
+            "name": "pkg",
+            "version": "1.0.0",
+            "bugs": "https://github.com/owner/pkg/issues",
+            "man": ["./man/foo.1", "./man/bar.1"],
+            "directories": {"lib": "lib", "bin": "bin"},
+            "repository": {"type": "git", "url": "https://github.com/owner/pkg.git", "directory": "packages/pkg"},
+            "bundledDependencies": true,
+            "dependencies": {"dep": "^1.0.0"}
+        }"#);
+        assert!(dropped.is_empty(), "{:?}", dropped);
+    }
+
+    #[test]
+    fn test_own_keys() {
+        let package_json: PackageJson = serde_json::from_str(r#"{
+            "name": "pkg",
+            "version": "1.0.0",
+            "url": "not the bugs url",
+            "bugs": {"email": "bugs@example.com"},
+            "man": "./man/pkg.1",
+            "lib": "not directories.lib",
+            "bundledDependencies": true,
+            "dependencies": {"a": "1", "b": "2"}
+        }"#).unwrap();
+        let bugs = package_json.bugs.as_ref().unwrap();
+        assert_eq!((bugs.url(), bugs.email()), (None, Some("bugs@example.com")));
+        assert!(matches!(&package_json.man, Some(Man::String(page)) if page == "./man/pkg.1"));
+        assert!(package_json.directories.is_none());
+        assert_eq!(package_json.bundled(), Some(("bundledDependencies", vec!["a".to_string(), "b".to_string()])));
+        let bugs: Bugs = serde_json::from_str(r#""https://example.com/issues""#).unwrap();
+        assert_eq!(bugs.url(), Some("https://example.com/issues"));
+
+        // without bugs or directories keys there are neither, whatever the top level holds
+        let package_json: PackageJson = serde_json::from_str(r#"{
+            "name": "pkg",
+            "version": "1.0.0",
+            "email": "not the bugs email",
+            "bin": "./cli.js"
+        }"#).unwrap();
+        assert!(package_json.bugs.is_none() && package_json.directories.is_none());
+        let package_json: PackageJson = serde_json::from_str(r#"{
+            "name": "pkg",
+            "version": "1.0.0",
+            "directories": {"lib": "./lib", "bin": "./bin"}
+        }"#).unwrap();
+        let directories = package_json.directories.unwrap();
+        assert_eq!((directories.lib.as_deref(), directories.bin.as_deref()), (Some("./lib"), Some("./bin")));
+    }
+
     #[test]
     fn test_parse_express() {
         let path = "src/drivers/npm/fixtures/express-4.17.1-package.json";
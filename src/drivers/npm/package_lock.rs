@@ -0,0 +1,327 @@
+// package-lock.json and npm-shrinkwrap.json, which share a format
+// https://docs.npmjs.com/cli/v9/configuring-npm/package-lock-json
+//
+//   lockfileVersion 1  npm 5 and 6, a "dependencies" tree nested as node_modules is
+//   lockfileVersion 2  npm 7 and 8, a flat "packages" map keyed by install path,
+//                      with the v1 tree alongside for older npm
+//   lockfileVersion 3  npm 9, "packages" alone
+use std::collections::BTreeMap as Map;
+use std::collections::{HashMap, HashSet};
+
+use serde::Deserialize;
+
+use super::resolved::{Edge, EdgeKind, LockfileError, ResolvedGraph, ResolvedPackage};
+use crate::version::Version;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageLock {
+    name: Option<String>,
+    version: Option<String>,
+    lockfile_version: u32,
+    packages: Option<Map<String, LockPackage>>,
+    dependencies: Option<Map<String, LockDependency>>,
+}
+
+// v2 and v3, keyed by install path: "" is the root project,
+// "node_modules/a/node_modules/b" is b as installed under a
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+struct LockPackage {
+    name: Option<String>, // when it differs from the path, for aliases and workspaces
+    version: Option<String>,
+    resolved: Option<String>,
+    integrity: Option<String>,
+    link: bool, // resolved is then the path it links to
+    dev: bool,
+    optional: bool,
+    dev_optional: bool, // dev, and optional for something not dev
+    peer: bool,
+    in_bundle: bool,
+    dependencies: Map<String, String>,
+    dev_dependencies: Map<String, String>, // the root and workspaces only
+    optional_dependencies: Map<String, String>,
+    peer_dependencies: Map<String, String>,
+}
+
+// v1, keyed by name and nested as installed
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+struct LockDependency {
+    version: String, // or "file:../path" for local packages
+    resolved: Option<String>,
+    integrity: Option<String>,
+    dev: bool,
+    optional: bool,
+    bundled: bool,
+    requires: Map<String, String>,
+    dependencies: Map<String, LockDependency>,
+}
+
+pub fn parse_package_lock(json: &str) -> Result<ResolvedGraph, LockfileError> {
+    let lock: PackageLock = serde_json::from_str(json).map_err(LockfileError::Json)?;
+    match lock.lockfile_version {
+        1 => {
+            let dependencies = lock.dependencies.as_ref().ok_or(LockfileError::Missing("dependencies"))?;
+            Ok(from_tree(&lock, dependencies))
+        }
+        2 | 3 => {
+            let packages = lock.packages.as_ref().ok_or(LockfileError::Missing("packages"))?;
+            Ok(from_packages(packages))
+        }
+        v => Err(LockfileError::UnsupportedVersion(v)),
+    }
+}
+
+fn from_packages(packages: &Map<String, LockPackage>) -> ResolvedGraph {
+    // the root first, then in path order
+    let mut entries: Vec<_> = packages.iter().collect();
+    entries.sort_by_key(|(path, _)| !path.is_empty());
+    let mut graph: Vec<ResolvedPackage> = entries.iter()
+        .map(|(path, p)| ResolvedPackage {
+            id: path.to_string(),
+            name: p.name.clone().unwrap_or_else(|| name_of(path).to_string()),
+            version: Version::classify(p.version.as_deref().unwrap_or("")),
+            resolved: p.resolved.clone().filter(|_| !p.link),
+            integrity: p.integrity.clone(),
+            dev: p.dev || p.dev_optional,
+            optional: p.optional || p.dev_optional,
+            peer: p.peer,
+            bundled: p.in_bundle,
+            link: p.resolved.clone().filter(|_| p.link),
+            dependencies: vec![],
+        })
+        .collect();
+    let index: HashMap<&str, usize> = entries.iter().enumerate().map(|(i, (path, _))| (path.as_str(), i)).collect();
+    for (i, (path, p)) in entries.iter().enumerate() {
+        let groups = [
+            (EdgeKind::Prod, &p.dependencies),
+            (EdgeKind::Dev, &p.dev_dependencies),
+            (EdgeKind::Optional, &p.optional_dependencies),
+            (EdgeKind::Peer, &p.peer_dependencies),
+        ];
+        graph[i].dependencies = groups.iter()
+            .flat_map(|(kind, deps)| deps.iter().map(move |(name, spec)| (*kind, name, spec)))
+            .map(|(kind, name, spec)| Edge {
+                name: name.clone(),
+                spec: Some(spec.clone()),
+                kind,
+                to: find_installed(&index, path, name),
+            })
+            .collect();
+    }
+    ResolvedGraph { packages: graph }
+}
+
+fn from_tree(lock: &PackageLock, dependencies: &Map<String, LockDependency>) -> ResolvedGraph {
+    // flattened into install paths, so both versions resolve the same way
+    let mut flat: Vec<(String, &str, &LockDependency)> = vec![];
+    fn walk<'a>(base: &str, deps: &'a Map<String, LockDependency>, flat: &mut Vec<(String, &'a str, &'a LockDependency)>) {
+        for (name, dep) in deps {
+            let path = match base {
+                "" => format!("node_modules/{}", name),
+                base => format!("{}/node_modules/{}", base, name),
+            };
+            flat.push((path.clone(), name, dep));
+            walk(&path, &dep.dependencies, flat);
+        }
+    }
+    walk("", dependencies, &mut flat);
+    let index: HashMap<&str, usize> = flat.iter().enumerate().map(|(i, (path, _, _))| (path.as_str(), i + 1)).collect();
+
+    let packages: Vec<_> = flat.iter().map(|(path, name, dep)| {
+        let link = dep.version.strip_prefix("file:").map(String::from);
+        ResolvedPackage {
+            id: path.clone(),
+            name: name.to_string(),
+            version: if link.is_some() { Version::Missing } else { Version::classify(&dep.version) },
+            resolved: dep.resolved.clone(),
+            integrity: dep.integrity.clone(),
+            dev: dep.dev,
+            optional: dep.optional,
+            peer: false, // v1 does not install peers
+            bundled: dep.bundled,
+            link,
+            dependencies: dep.requires.iter()
+                .map(|(name, spec)| Edge {
+                    name: name.clone(),
+                    spec: Some(spec.clone()),
+                    kind: EdgeKind::Prod,
+                    to: find_installed(&index, path, name),
+                })
+                .collect(),
+        }
+    }).collect();
+
+    // v1 does not record the root's own dependencies, only what is at the
+    // top, hoisted packages included. Those no installed package resolves to
+    // are taken as the root's, without specs as dep.version is what got
+    // installed. A direct dependency that something else also resolves to
+    // is left out.
+    let reached: HashSet<usize> = packages.iter()
+        .flat_map(|p| p.dependencies.iter().filter_map(|e| e.to))
+        .collect();
+    let root = ResolvedPackage {
+        id: String::new(),
+        name: lock.name.clone().unwrap_or_default(),
+        version: Version::classify(lock.version.as_deref().unwrap_or("")),
+        resolved: None,
+        integrity: None,
+        dev: false,
+        optional: false,
+        peer: false,
+        bundled: false,
+        link: None,
+        dependencies: dependencies.iter()
+            .map(|(name, dep)| (name, dep, find_installed(&index, "", name)))
+            .filter(|(_, _, to)| to.is_none_or(|i| !reached.contains(&i)))
+            .map(|(name, dep, to)| Edge {
+                name: name.clone(),
+                spec: None,
+                kind: match (dep.dev, dep.optional) {
+                    (true, _) => EdgeKind::Dev,
+                    (false, true) => EdgeKind::Optional,
+                    (false, false) => EdgeKind::Prod,
+                },
+                to,
+            })
+            .collect(),
+    };
+    ResolvedGraph { packages: std::iter::once(root).chain(packages).collect() }
+}
+
+// Where node finds name from a package installed at path: in its own
+// node_modules, then in each enclosing one up to the root's.
+fn find_installed(index: &HashMap<&str, usize>, path: &str, name: &str) -> Option<usize> {
+    let mut base = path;
+    loop {
+        let candidate = match base {
+            "" => format!("node_modules/{}", name),
+            base => format!("{}/node_modules/{}", base, name),
+        };
+        if let Some(i) = index.get(candidate.as_str()) {
+            return Some(*i);
+        }
+        if base.is_empty() {
+            return None;
+        }
+        base = base.rfind("node_modules/").map_or("", |i| base[..i].trim_end_matches('/'));
+    }
+}
+
+// "node_modules/a/node_modules/@scope/b" => "@scope/b"
+fn name_of(path: &str) -> &str {
+    path.rfind("node_modules/").map_or(path, |i| &path[i + "node_modules/".len()..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1: &str = r#"{
+        "name": "app", "version": "1.0.0", "lockfileVersion": 1, "requires": true,
+        "dependencies": {
+            "a": {"version": "1.0.0", "resolved": "https://registry.npmjs.org/a/-/a-1.0.0.tgz",
+                  "integrity": "sha512-aaa", "requires": {"b": "^2.0.0", "c": "^1.0.0"},
+                  "dependencies": {"b": {"version": "2.1.0", "integrity": "sha512-b21"}}},
+            "b": {"version": "1.0.0", "dev": true},
+            "c": {"version": "1.0.0"},
+            "local": {"version": "file:../local"}
+        }
+    }"#;
+
+    const V3: &str = r#"{
+        "name": "app", "version": "1.0.0", "lockfileVersion": 3, "requires": true,
+        "packages": {
+            "": {"name": "app", "version": "1.0.0", "workspaces": ["packages/*"],
+                 "dependencies": {"a": "^1.0.0", "ws": "*"}, "devDependencies": {"b": "^1.0.0"}},
+            "node_modules/a": {"version": "1.0.0", "resolved": "https://registry.npmjs.org/a/-/a-1.0.0.tgz",
+                               "integrity": "sha512-aaa", "dependencies": {"b": "^2.0.0", "c": "^1.0.0"},
+                               "peerDependencies": {"react": "*"}},
+            "node_modules/a/node_modules/b": {"version": "2.1.0", "inBundle": true},
+            "node_modules/b": {"version": "1.0.0", "dev": true},
+            "node_modules/@scope/c": {"version": "1.0.0", "devOptional": true},
+            "node_modules/c": {"name": "@scope/c", "version": "1.0.0", "optional": true},
+            "node_modules/ws": {"resolved": "packages/ws", "link": true},
+            "packages/ws": {"name": "ws", "version": "0.1.0", "dependencies": {"a": "^1.0.0"}}
+        }
+    }"#;
+
+    fn edges(graph: &ResolvedGraph, id: &str) -> Vec<(String, Option<String>)> {
+        graph.get(id).unwrap().dependencies.iter()
+            .map(|e| (e.name.clone(), e.to.map(|i| graph.packages[i].id.clone())))
+            .collect()
+    }
+
+    fn some(id: &str) -> Option<String> {
+        Some(id.to_string())
+    }
+
+    #[test]
+    fn test_package_lock_v1() {
+        let graph = parse_package_lock(V1).unwrap();
+        let root = graph.root().unwrap();
+        assert_eq!((root.name.as_str(), root.version.to_string()), ("app", "1.0.0".to_string()));
+        assert_eq!(edges(&graph, ""), [
+            ("a".to_string(), some("node_modules/a")),
+            ("b".to_string(), some("node_modules/b")),
+            ("local".to_string(), some("node_modules/local")),
+        ]);
+        // c is only there as a's, hoisted
+        assert_eq!(edges(&graph, "node_modules/a"), [
+            ("b".to_string(), some("node_modules/a/node_modules/b")),
+            ("c".to_string(), some("node_modules/c")),
+        ]);
+        assert_eq!(graph.root().unwrap().dependencies[1].kind, EdgeKind::Dev);
+        let a = graph.get("node_modules/a").unwrap();
+        assert_eq!((a.integrity.as_deref(), a.dev), (Some("sha512-aaa"), false));
+        assert!(graph.get("node_modules/b").unwrap().dev);
+        assert_eq!(graph.get("node_modules/local").unwrap().link.as_deref(), Some("../local"));
+        let local = &root.dependencies[2];
+        assert_eq!(graph.target(local).unwrap().id, "node_modules/local");
+        assert!(root.dependencies.iter().all(|e| e.spec.is_none()));
+        assert_eq!(graph.get("node_modules/a").unwrap().dependencies[0].spec.as_deref(), Some("^2.0.0"));
+        assert_eq!(graph.versions("b").iter().map(|v| v.to_string()).collect::<Vec<_>>(), ["1.0.0", "2.1.0"]);
+    }
+
+    #[test]
+    fn test_package_lock_v2_v3() {
+        let v2 = V3.replace(r#""lockfileVersion": 3"#, r#""lockfileVersion": 2"#)
+            .replace(r#""requires": true,"#, r#""requires": true, "dependencies": {},"#);
+        for json in [V3, v2.as_str()].iter() {
+            let graph = parse_package_lock(json).unwrap();
+            assert_eq!(graph.packages.len(), 8);
+            assert_eq!(edges(&graph, ""), [
+                ("a".to_string(), some("node_modules/a")),
+                ("ws".to_string(), some("node_modules/ws")),
+                ("b".to_string(), some("node_modules/b")),
+            ]);
+            assert_eq!(edges(&graph, "node_modules/a"), [
+                ("b".to_string(), some("node_modules/a/node_modules/b")),
+                ("c".to_string(), some("node_modules/c")),
+                ("react".to_string(), None),
+            ]);
+            assert_eq!(edges(&graph, "packages/ws"), [("a".to_string(), some("node_modules/a"))]);
+
+            let c = graph.get("node_modules/c").unwrap();
+            assert_eq!((c.name.as_str(), c.optional, c.dev), ("@scope/c", true, false));
+            let scoped = graph.get("node_modules/@scope/c").unwrap();
+            assert_eq!((scoped.name.as_str(), scoped.optional, scoped.dev), ("@scope/c", true, true));
+            assert!(graph.get("node_modules/a/node_modules/b").unwrap().bundled);
+            let ws = &graph.root().unwrap().dependencies[1];
+            assert_eq!(graph.target(ws).unwrap().id, "packages/ws");
+            assert_eq!(graph.get("node_modules/ws").unwrap().resolved, None);
+        }
+    }
+
+    #[test]
+    fn test_package_lock_errors() {
+        assert_eq!(parse_package_lock(r#"{"lockfileVersion": 4}"#).unwrap_err().to_string(),
+                   "unsupported lockfileVersion 4");
+        assert_eq!(parse_package_lock(r#"{"lockfileVersion": 3}"#).unwrap_err().to_string(),
+                   "lockfile has no `packages`");
+        assert!(parse_package_lock("{}").is_err());
+    }
+}
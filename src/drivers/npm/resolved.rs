@@ -0,0 +1,104 @@
+// What a lockfile says got installed, whichever package manager wrote it:
+// every package once, with the edges its dependencies resolved along.
+use std::error::Error;
+use std::fmt;
+
+use crate::version::Version;
+
+#[derive(Debug)]
+pub enum LockfileError {
+    Json(serde_json::Error),
+    Yaml(serde_yaml::Error),
+    Syntax(usize, String), // line number, from 1, and what is wrong there
+    UnsupportedVersion(u32),
+    Missing(&'static str), // a key this lockfile version requires
+}
+
+impl fmt::Display for LockfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LockfileError::Json(e) => write!(f, "invalid lockfile: {}", e),
+            LockfileError::Yaml(e) => write!(f, "invalid lockfile: {}", e),
+            LockfileError::Syntax(line, what) => write!(f, "invalid lockfile at line {}: {}", line, what),
+            LockfileError::UnsupportedVersion(v) => write!(f, "unsupported lockfileVersion {}", v),
+            LockfileError::Missing(key) => write!(f, "lockfile has no `{}`", key),
+        }
+    }
+}
+
+impl Error for LockfileError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedGraph {
+    pub packages: Vec<ResolvedPackage>, // the root project first
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedPackage {
+    // unique in the graph: npm's install path, berry's resolution or classic
+    // yarn's "name@version", which a copy from git or a tarball sharing it
+    // with the registry copy replaces with its descriptor
+    pub id: String,
+    pub name: String,
+    pub version: Version,
+    pub resolved: Option<String>,  // where it was fetched from
+    pub integrity: Option<String>, // "sha512-..." subresource integrity
+    pub dev: bool,                 // only needed for development
+    pub optional: bool,
+    pub peer: bool,
+    pub bundled: bool,             // shipped inside its dependent's tarball
+    pub link: Option<String>,      // the id it links to, or a path outside the graph ("../local")
+    pub dependencies: Vec<Edge>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub name: String,
+    pub spec: Option<String>, // as the dependent declares it; None when the lockfile does not record it
+    pub kind: EdgeKind,
+    pub to: Option<usize>,    // index in packages; None when it is not installed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    Prod,
+    Dev,
+    Optional,
+    Peer,
+}
+
+impl ResolvedGraph {
+    pub fn root(&self) -> Option<&ResolvedPackage> {
+        self.packages.first()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ResolvedPackage> {
+        self.packages.iter().find(|p| p.id == id)
+    }
+
+    // What the edge leads to, through links. A link to a path outside the
+    // graph ends at the link itself, as nothing more is known about it.
+    pub fn target(&self, edge: &Edge) -> Option<&ResolvedPackage> {
+        let mut package = self.packages.get(edge.to?)?;
+        let mut hops = 0;
+        while let Some(next) = package.link.as_ref().and_then(|link| self.get(link)) {
+            package = next;
+            hops += 1;
+            if hops > self.packages.len() {
+                return None; // a cycle of links
+            }
+        }
+        Some(package)
+    }
+
+    // The versions of name installed, e.g. to count duplicates
+    pub fn versions(&self, name: &str) -> Vec<&Version> {
+        let mut versions: Vec<_> = self.packages.iter().skip(1) // not the root
+            .filter(|p| p.name == name && p.link.is_none())
+            .map(|p| &p.version)
+            .collect();
+        versions.sort();
+        versions.dedup();
+        versions
+    }
+}
@@ -0,0 +1,411 @@
+// yarn.lock, in either of the formats yarn has written:
+//
+// Classic, yarn 1, its own indented format with every descriptor that
+// resolved to an entry in its header:
+//
+//   "lodash@^4.17.0", lodash@^4.17.15:
+//     version "4.17.21"
+//     resolved "https://registry.yarnpkg.com/lodash/-/lodash-4.17.21.tgz#679591c5..."
+//     integrity sha512-v2kDEe57...
+//     dependencies:
+//       foo "^1.0.0"
+//
+// Berry, yarn 2 and later, YAML with a __metadata entry:
+//
+//   "lodash@npm:^4.17.0, lodash@npm:^4.17.15":
+//     version: 4.17.21
+//     resolution: "lodash@npm:4.17.21"
+//     checksum: 6b67e805...
+//     linkType: hard
+use std::collections::BTreeMap as Map;
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_yaml::Value;
+
+use super::dep_spec::NpmDepSpec;
+use super::resolved::{Edge, EdgeKind, LockfileError, ResolvedGraph, ResolvedPackage};
+use crate::version::Version;
+
+// An entry of either format, before the graph is built
+#[derive(Debug, Default)]
+struct Entry {
+    descriptors: Vec<String>, // "lodash@^4.17.0"
+    id: String,
+    version: String,
+    resolved: Option<String>,
+    integrity: Option<String>,
+    link: Option<String>,
+    dependencies: Vec<(EdgeKind, String, String)>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+struct BerryEntry {
+    version: Option<Value>, // a string, unless YAML reads it as a number
+    resolution: Option<String>,
+    checksum: Option<Value>,
+    link_type: Option<String>, // "hard", or "soft" for workspaces and links
+    dependencies: Map<String, Value>,
+    peer_dependencies: Map<String, Value>,
+    dependencies_meta: Map<String, DependencyMeta>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DependencyMeta {
+    optional: bool,
+}
+
+pub fn parse_yarn_lock(text: &str) -> Result<ResolvedGraph, LockfileError> {
+    let entries = if text.lines().any(|l| l.starts_with("__metadata:")) {
+        parse_berry(text)?
+    } else {
+        parse_classic(text)?
+    };
+    Ok(to_graph(entries))
+}
+
+fn parse_classic(text: &str) -> Result<Vec<Entry>, LockfileError> {
+    let mut entries: Vec<Entry> = vec![];
+    let mut group: Option<EdgeKind> = None;
+    for (n, line) in text.lines().enumerate() {
+        let error = |what: &str| LockfileError::Syntax(n + 1, what.to_string());
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - content.len();
+        let content = content.trim_end();
+        match indent {
+            0 => {
+                let header = content.strip_suffix(':').ok_or_else(|| error("expected `:` after the descriptors"))?;
+                let descriptors = split_descriptors(header).ok_or_else(|| error("invalid descriptor"))?;
+                entries.push(Entry { descriptors, ..Entry::default() });
+                group = None;
+            }
+            2 => {
+                let entry = entries.last_mut().ok_or_else(|| error("field outside an entry"))?;
+                let (key, value) = split_field(content).ok_or_else(|| error("invalid field"))?;
+                group = None;
+                match (key.as_str(), value) {
+                    ("dependencies", None) => group = Some(EdgeKind::Prod),
+                    ("optionalDependencies", None) => group = Some(EdgeKind::Optional),
+                    ("peerDependencies", None) => group = Some(EdgeKind::Peer),
+                    ("version", Some(v)) => entry.version = v,
+                    ("resolved", Some(v)) => entry.resolved = Some(v),
+                    ("integrity", Some(v)) => entry.integrity = Some(v),
+                    _ => {} // e.g. uid, or bin: and the other groups
+                }
+            }
+            4 => {
+                let entry = entries.last_mut().ok_or_else(|| error("dependency outside an entry"))?;
+                if let Some(kind) = group {
+                    match split_field(content) {
+                        Some((name, Some(spec))) => entry.dependencies.push((kind, name, spec)),
+                        _ => return Err(error("invalid dependency")),
+                    }
+                }
+            }
+            _ => return Err(error("unexpected indentation")),
+        }
+    }
+    // "name@version", unless a copy from another source shares it, as a
+    // git or tarball dependency may. The registry copy, or else the first,
+    // keeps it and the others take their first descriptor, which no other
+    // entry has.
+    let ids: Vec<String> = entries.iter()
+        .map(|e| format!("{}@{}", split_descriptor(&e.descriptors[0]).0, e.version))
+        .collect();
+    let from_registry: Vec<bool> = entries.iter()
+        .map(|e| NpmDepSpec::parse(split_descriptor(&e.descriptors[0]).1).is_ok_and(|s| s.is_registry()))
+        .collect();
+    for (i, entry) in entries.iter_mut().enumerate() {
+        let copies: Vec<usize> = (0..ids.len()).filter(|&j| ids[j] == ids[i]).collect();
+        let keeper = copies.iter().find(|&&j| from_registry[j]).unwrap_or(&copies[0]);
+        entry.id = if *keeper == i { ids[i].clone() } else { entry.descriptors[0].clone() };
+    }
+    Ok(entries)
+}
+
+fn parse_berry(text: &str) -> Result<Vec<Entry>, LockfileError> {
+    let lock: Map<String, Value> = serde_yaml::from_str(text).map_err(LockfileError::Yaml)?;
+    let mut entries = vec![];
+    for (key, value) in lock.into_iter().filter(|(key, _)| key != "__metadata") {
+        let berry: BerryEntry = serde_yaml::from_value(value).map_err(LockfileError::Yaml)?;
+        let descriptors: Vec<String> = key.split(',').map(|d| d.trim().to_string()).collect();
+        let resolution = berry.resolution.clone().unwrap_or_else(|| descriptors[0].clone());
+        // "pkg@link:../pkg", but not workspaces, which are in the graph themselves
+        let protocol = split_descriptor(&resolution).1.split("::").next().unwrap_or("");
+        let link = match berry.link_type.as_deref() {
+            Some("soft") if !protocol.starts_with("workspace:") => {
+                Some(protocol.split_once(':').map_or(protocol, |(_, path)| path).to_string())
+            }
+            _ => None,
+        };
+        let optional = |name: &String| berry.dependencies_meta.get(name).is_some_and(|m| m.optional);
+        let dependencies = berry.dependencies.iter()
+            .map(|(name, spec)| (if optional(name) { EdgeKind::Optional } else { EdgeKind::Prod }, name, spec))
+            .chain(berry.peer_dependencies.iter().map(|(name, spec)| (EdgeKind::Peer, name, spec)))
+            .map(|(kind, name, spec)| (kind, name.clone(), scalar(spec)))
+            .collect();
+        entries.push(Entry {
+            descriptors,
+            id: resolution.clone(),
+            version: berry.version.as_ref().map(scalar).unwrap_or_default(),
+            resolved: Some(resolution),
+            integrity: berry.checksum.as_ref().map(scalar),
+            link,
+            dependencies,
+        });
+    }
+    Ok(entries)
+}
+
+// The root is the workspace at "." in berry; classic lockfiles do not
+// record it, so there it has no name and no edges.
+fn to_graph(mut entries: Vec<Entry>) -> ResolvedGraph {
+    let root = entries.iter().position(|e| e.descriptors.iter().any(|d| split_descriptor(d).1 == "workspace:."));
+    let root = match root {
+        Some(i) => entries.remove(i),
+        None => Entry::default(),
+    };
+    entries.insert(0, root);
+    // berry binds links to the workspace they are in, "local@link:../local::locator=..."
+    let index: HashMap<&str, usize> = entries.iter().enumerate()
+        .flat_map(|(i, e)| e.descriptors.iter().map(move |d| (d.split("::").next().unwrap_or(d), i)))
+        .collect();
+    // berry's descriptors carry the protocol its dependencies may leave out
+    let find = |name: &str, spec: &str| {
+        index.get(format!("{}@{}", name, spec).as_str())
+            .or_else(|| index.get(format!("{}@npm:{}", name, spec).as_str()))
+            .copied()
+    };
+    let packages = entries.iter()
+        .map(|e| ResolvedPackage {
+            id: e.id.clone(),
+            name: e.descriptors.first().map_or("", |d| split_descriptor(d).0).to_string(),
+            version: Version::classify(&e.version),
+            resolved: e.resolved.clone(),
+            integrity: e.integrity.clone(),
+            dev: false, // neither format records it
+            optional: false,
+            peer: false,
+            bundled: false,
+            link: e.link.clone(),
+            dependencies: e.dependencies.iter()
+                .map(|(kind, name, spec)| Edge { name: name.clone(), spec: Some(spec.clone()), kind: *kind, to: find(name, spec) })
+                .collect(),
+        })
+        .collect();
+    ResolvedGraph { packages }
+}
+
+// "@scope/name@npm:^1.0.0" => ("@scope/name", "npm:^1.0.0")
+fn split_descriptor(descriptor: &str) -> (&str, &str) {
+    match descriptor.get(1..).and_then(|d| d.find('@')) {
+        Some(i) => (&descriptor[..i + 1], &descriptor[i + 2..]),
+        None => (descriptor, ""),
+    }
+}
+
+// `"lodash@^4.17.0", lodash@^4.17.15` => ["lodash@^4.17.0", "lodash@^4.17.15"]
+fn split_descriptors(header: &str) -> Option<Vec<String>> {
+    let mut descriptors = vec![];
+    let mut rest = header.trim();
+    while !rest.is_empty() {
+        let (descriptor, after) = token(rest, ',')?;
+        descriptors.push(descriptor);
+        rest = after.trim_start().strip_prefix(',').unwrap_or(after).trim_start();
+    }
+    if descriptors.is_empty() { None } else { Some(descriptors) }
+}
+
+// `version "4.17.21"` => ("version", Some("4.17.21")), `dependencies:` => ("dependencies", None)
+fn split_field(line: &str) -> Option<(String, Option<String>)> {
+    if let Some(key) = line.strip_suffix(':') {
+        return Some((token(key, ' ')?.0, None));
+    }
+    let (key, rest) = token(line, ' ')?;
+    let (value, _) = token(rest.trim_start(), '\0')?;
+    Some((key, Some(value)))
+}
+
+// A quoted string, with JSON escapes, or everything up to end; and what follows
+fn token(s: &str, end: char) -> Option<(String, &str)> {
+    if s.starts_with('"') {
+        let mut escaped = false;
+        let close = s.char_indices().skip(1).find(|&(_, c)| {
+            let found = c == '"' && !escaped;
+            escaped = c == '\\' && !escaped;
+            found
+        })?.0;
+        return Some((serde_json::from_str(&s[..=close]).ok()?, &s[close + 1..]));
+    }
+    let i = s.find(end).unwrap_or(s.len());
+    let token = s[..i].trim_end();
+    if token.is_empty() { None } else { Some((token.to_string(), &s[i..])) }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLASSIC: &str = r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@babel/code-frame@^7.0.0", "@babel/code-frame@^7.8.3":
+  version "7.8.3"
+  resolved "https://registry.yarnpkg.com/@babel/code-frame/-/code-frame-7.8.3.tgz#33e25903"
+  integrity sha512-a9gxpmdXtZEInkCSHUJDLHZVBgb1QS0jhss4cPP93EW7s+uC5bikET2twEF3KV+7rDblJcmNvTR7VJejqd2C2g==
+  dependencies:
+    "@babel/highlight" "^7.8.3"
+  optionalDependencies:
+    fsevents "^1.2.7"
+
+"@babel/highlight@^7.8.3":
+  version "7.9.0"
+  resolved "https://registry.yarnpkg.com/@babel/highlight/-/highlight-7.9.0.tgz#4e9b45cc"
+
+lodash@^4.17.0, lodash@^4.17.15:
+  version "4.17.21"
+"#;
+
+    const BERRY: &str = r#"# This file is generated by running "yarn install" inside your project.
+
+__metadata:
+  version: 6
+  cacheKey: 8
+
+"app@workspace:.":
+  version: 0.0.0-use.local
+  resolution: "app@workspace:."
+  dependencies:
+    lodash: ^4.17.0
+    fsevents: ^2.3.2
+    local: "link:../local"
+    ws: "workspace:packages/ws"
+  dependenciesMeta:
+    fsevents:
+      optional: true
+  languageName: unknown
+  linkType: soft
+
+"fsevents@npm:^2.3.2":
+  version: 2.3.2
+  resolution: "fsevents@npm:2.3.2"
+  checksum: 97ade64e75091afee5265e6956cb72ba34db7819b4c3e94c431d4be2b19b8bb7a2d4116da417950c3425f17c8fe693d25e20212cac583ac1521ad066b77ae31f
+  languageName: node
+  linkType: hard
+
+"local@link:../local::locator=app%40workspace%3A.":
+  version: 0.0.0-use.local
+  resolution: "local@link:../local::locator=app%40workspace%3A."
+  languageName: node
+  linkType: soft
+
+"lodash@npm:^4.17.0, lodash@npm:^4.17.15":
+  version: 4.17.21
+  resolution: "lodash@npm:4.17.21"
+  checksum: eb835a2e51d381e561e508ce932ea50a8e5a68f4ebdd771ea240d3048244a8d13658acbd502cd4829768c56f2e16bdd4340b9ea141297d472517b83868e677f7
+  languageName: node
+  linkType: hard
+
+"ws@workspace:packages/ws":
+  version: 0.0.0-use.local
+  resolution: "ws@workspace:packages/ws"
+  peerDependencies:
+    lodash: "*"
+  languageName: unknown
+  linkType: soft
+"#;
+
+    fn edges(graph: &ResolvedGraph, i: usize) -> Vec<(String, EdgeKind, Option<String>)> {
+        graph.packages[i].dependencies.iter()
+            .map(|e| (e.name.clone(), e.kind, e.to.map(|i| graph.packages[i].id.clone())))
+            .collect()
+    }
+
+    #[test]
+    fn test_classic() {
+        let graph = parse_yarn_lock(CLASSIC).unwrap();
+        let ids: Vec<_> = graph.packages.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["", "@babel/code-frame@7.8.3", "@babel/highlight@7.9.0", "lodash@4.17.21"]);
+        assert_eq!(edges(&graph, 1), [
+            ("@babel/highlight".to_string(), EdgeKind::Prod, Some("@babel/highlight@7.9.0".to_string())),
+            ("fsevents".to_string(), EdgeKind::Optional, None),
+        ]);
+        let frame = &graph.packages[1];
+        assert_eq!((frame.name.as_str(), frame.version.to_string()), ("@babel/code-frame", "7.8.3".to_string()));
+        assert!(frame.integrity.as_deref().unwrap().starts_with("sha512-a9gx"));
+        assert!(graph.packages[3].resolved.is_none());
+    }
+
+    #[test]
+    fn test_classic_same_version() {
+        // the registry copy and a git copy of the same version keep apart,
+        // the registry one under "name@version" even when listed last
+        let graph = parse_yarn_lock(r#"
+a@^1.0.0:
+  version "1.0.0"
+  dependencies:
+    lodash "lodash/lodash#2da024c"
+
+"lodash@lodash/lodash#2da024c":
+  version "4.17.21"
+  resolved "https://codeload.github.com/lodash/lodash/tar.gz/2da024c"
+
+lodash@^4.17.0:
+  version "4.17.21"
+  resolved "https://registry.yarnpkg.com/lodash/-/lodash-4.17.21.tgz#679591c5"
+"#).unwrap();
+        let ids: Vec<_> = graph.packages.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["", "a@1.0.0", "lodash@lodash/lodash#2da024c", "lodash@4.17.21"]);
+        let git = graph.target(&graph.get("a@1.0.0").unwrap().dependencies[0]).unwrap();
+        assert!(git.resolved.as_deref().unwrap().starts_with("https://codeload.github.com/"));
+        let registry = graph.get("lodash@4.17.21").unwrap();
+        assert!(registry.resolved.as_deref().unwrap().starts_with("https://registry.yarnpkg.com/"));
+    }
+
+    #[test]
+    fn test_classic_errors() {
+        let error = |text: &str| parse_yarn_lock(text).unwrap_err().to_string();
+        assert_eq!(error("lodash@^4.17.0\n  version \"4.17.21\"\n"),
+                   "invalid lockfile at line 1: expected `:` after the descriptors");
+        assert_eq!(error("  version \"4.17.21\"\n"), "invalid lockfile at line 1: field outside an entry");
+        assert_eq!(error("a@1:\n   version \"1\"\n"), "invalid lockfile at line 2: unexpected indentation");
+    }
+
+    #[test]
+    fn test_berry() {
+        let graph = parse_yarn_lock(BERRY).unwrap();
+        let root = graph.root().unwrap();
+        assert_eq!((root.id.as_str(), root.name.as_str()), ("app@workspace:.", "app"));
+        assert_eq!(edges(&graph, 0), [
+            ("fsevents".to_string(), EdgeKind::Optional, Some("fsevents@npm:2.3.2".to_string())),
+            ("local".to_string(), EdgeKind::Prod, Some("local@link:../local::locator=app%40workspace%3A.".to_string())),
+            ("lodash".to_string(), EdgeKind::Prod, Some("lodash@npm:4.17.21".to_string())),
+            ("ws".to_string(), EdgeKind::Prod, Some("ws@workspace:packages/ws".to_string())),
+        ]);
+        let lodash = graph.get("lodash@npm:4.17.21").unwrap();
+        assert_eq!((lodash.name.as_str(), lodash.version.to_string()), ("lodash", "4.17.21".to_string()));
+        assert!(lodash.integrity.as_deref().unwrap().starts_with("eb835a2e"));
+        let ws = graph.get("ws@workspace:packages/ws").unwrap();
+        assert_eq!((ws.link.as_deref(), ws.dependencies[0].kind), (None, EdgeKind::Peer));
+        let local = graph.get("local@link:../local::locator=app%40workspace%3A.").unwrap();
+        assert_eq!(local.link.as_deref(), Some("../local"));
+        assert_eq!(graph.target(&root.dependencies[1]), Some(local));
+        assert!(parse_yarn_lock("__metadata:\n  version: 6\n\"a@npm:1\": [").is_err());
+    }
+}
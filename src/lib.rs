@@ -0,0 +1,78 @@
+// the original parser tests trip these lints and are kept as written
+#![cfg_attr(test, allow(unused_variables, clippy::single_match))]
+extern crate pest;
+#[macro_use]
+extern crate pest_derive;
+pub mod drivers;
+pub mod version;
+
+use serde::{Deserialize, Serialize};
+
+use drivers::npm::hosted_git::HostedGit;
+use version::{Version, VersionQuery};
+
+#[derive(Serialize, Deserialize)]
+pub struct PkgInfo {
+    pub name: String,
+    pub version: Version,
+    pub description: Option<String>,
+    pub keywords: Option<Vec<String>>,
+    pub homepage: Option<String>,
+    pub bugs: Option<Vec<Bugs>>,
+    pub license: Option<String>,
+    pub people: Option<Vec<Person>>,
+    pub funding: Option<Vec<Funding>>,
+    pub repository: Option<Repository>,
+    pub dependencies: Vec<DepGroup>,
+    pub scripts: Option<Vec<ScriptGroup>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ScriptGroup {
+    pub name: String,
+    pub scripts: Vec<String>, // commands, in the order they run
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Repository {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub url: String,               // normalized, see hosted_git::normalize
+    pub directory: Option<String>, // where the package is, in monorepos
+    pub hosted: Option<HostedGit>, // on github, gitlab, bitbucket or a gist
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DepGroup {
+    pub name: String, // default, production, build, test, etc.
+    pub deps: Vec<Dep>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Dep {
+    pub name: String,
+    pub version: Option<VersionQuery>, // None for git, URL and path dependencies
+    pub spec: String,                  // as written in the manifest
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Person {
+    pub name: String,
+    pub role: String, // author, contributor, maintainer, etc.
+    pub homepage: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Funding {
+    #[serde(rename = "type")]
+    pub type_: Option<String>, // github, patreon, opencollective, etc.
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bugs {
+    Email(String),
+    Url(String),
+}
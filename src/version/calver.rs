@@ -0,0 +1,278 @@
+// Format-string driven calendar versions
+// https://calver.org/#scheme
+//
+//   CalVer::parse("YYYY.0M.0D", "2020.06.07")
+//   CalVer::parse("YY.0M.MICRO", "20.04.1")
+//
+// Anything after the format may be a "-pre_release" and/or "+build" tag.
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+use super::{cmp_pre_release, CalVer};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalVerError {
+    Format(String), // unknown token in the format string
+    Mismatch { position: usize, expected: &'static str },
+    OutOfRange { position: usize, field: &'static str },
+}
+
+impl fmt::Display for CalVerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CalVerError::Format(format) => write!(f, "invalid calver format \"{}\"", format),
+            CalVerError::Mismatch { position, expected } => {
+                write!(f, "expected {} at column {}", expected, position + 1)
+            }
+            CalVerError::OutOfRange { position, field } => {
+                write!(f, "{} out of range at column {}", field, position + 1)
+            }
+        }
+    }
+}
+
+impl Error for CalVerError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    FullYear,   // YYYY  2006, 2016, 2106
+    ShortYear,  // YY    6, 16, 106
+    PaddedYear, // 0Y    06, 16, 106
+    Month,      // MM    1, 2 ... 11, 12
+    PaddedMonth,// 0M    01, 02 ... 11, 12
+    Week,       // WW    1, 2, 33, 52
+    PaddedWeek, // 0W    01, 02, 33, 52
+    Day,        // DD    1, 2 ... 30, 31
+    PaddedDay,  // 0D    01, 02 ... 30, 31
+    Major,
+    Minor,
+    Micro,
+    Separator(char),
+}
+
+const TOKENS: [(&str, Token); 15] = [
+    ("YYYY", Token::FullYear),
+    ("YY", Token::ShortYear),
+    ("0Y", Token::PaddedYear),
+    ("MM", Token::Month),
+    ("0M", Token::PaddedMonth),
+    ("WW", Token::Week),
+    ("0W", Token::PaddedWeek),
+    ("DD", Token::Day),
+    ("0D", Token::PaddedDay),
+    ("MAJOR", Token::Major),
+    ("MINOR", Token::Minor),
+    ("MICRO", Token::Micro),
+    (".", Token::Separator('.')),
+    ("-", Token::Separator('-')),
+    ("_", Token::Separator('_')),
+];
+
+fn tokenize(format: &str) -> Result<Vec<Token>, CalVerError> {
+    let mut tokens = vec![];
+    let mut rest = format;
+    while !rest.is_empty() {
+        match TOKENS.iter().find(|(s, _)| rest.starts_with(s)) {
+            Some((s, token)) => {
+                tokens.push(*token);
+                rest = &rest[s.len()..];
+            }
+            None => return Err(CalVerError::Format(format.to_string())),
+        }
+    }
+    if !tokens.iter().any(|t| matches!(t, Token::FullYear | Token::ShortYear | Token::PaddedYear)) {
+        return Err(CalVerError::Format(format.to_string()));
+    }
+    Ok(tokens)
+}
+
+impl CalVer {
+    pub fn parse(format: &str, input: &str) -> Result<CalVer, CalVerError> {
+        let mut v = CalVer {
+            format: format.to_string(),
+            year: 0,
+            month: None,
+            week: None,
+            day: None,
+            major: None,
+            minor: None,
+            micro: None,
+            pre_release: None,
+            build: None,
+        };
+        let mut pos = 0;
+        for token in tokenize(format)? {
+            if let Token::Separator(c) = token {
+                if !input[pos..].starts_with(c) {
+                    return Err(CalVerError::Mismatch { position: pos, expected: "separator" });
+                }
+                pos += c.len_utf8();
+                continue;
+            }
+            let digits = input[pos..].bytes().take_while(|c| c.is_ascii_digit()).count();
+            let s = &input[pos..pos + digits];
+            let padded = matches!(token, Token::PaddedYear | Token::PaddedMonth | Token::PaddedWeek | Token::PaddedDay);
+            let well_formed = match token {
+                Token::FullYear => digits == 4,
+                Token::PaddedYear => digits == 2 || digits == 3,
+                // years 2000 to 2999, like 0Y
+                Token::ShortYear => (1..=3).contains(&digits) && (digits == 1 || !s.starts_with('0')),
+                _ if padded => digits == 2,
+                _ => digits > 0 && (digits == 1 || !s.starts_with('0')),
+            };
+            if !well_formed {
+                let expected = if padded { "zero-padded number" } else { "number" };
+                return Err(CalVerError::Mismatch { position: pos, expected });
+            }
+            let n: u32 = s.parse()
+                .map_err(|_| CalVerError::OutOfRange { position: pos, field: "number" })?;
+            let out_of_range = |field| CalVerError::OutOfRange { position: pos, field };
+            match token {
+                Token::FullYear => v.year = n as u16,
+                Token::ShortYear | Token::PaddedYear => v.year = 2000 + n as u16,
+                Token::Month | Token::PaddedMonth => match n {
+                    1..=12 => v.month = Some(n as u8),
+                    _ => return Err(out_of_range("month")),
+                },
+                Token::Week | Token::PaddedWeek => match n {
+                    1..=53 => v.week = Some(n as u8),
+                    _ => return Err(out_of_range("week")),
+                },
+                Token::Day | Token::PaddedDay => match n {
+                    1..=31 => v.day = Some(n as u8),
+                    _ => return Err(out_of_range("day")),
+                },
+                Token::Major => v.major = Some(n),
+                Token::Minor => v.minor = Some(n),
+                Token::Micro => v.micro = Some(n),
+                Token::Separator(_) => unreachable!(),
+            }
+            pos += digits;
+        }
+        let rest = &input[pos..];
+        let (rest, build) = match rest.find('+') {
+            Some(i) => (&rest[..i], Some(&rest[i + 1..])),
+            None => (rest, None),
+        };
+        let pre_release = match rest {
+            "" => None,
+            _ if rest.starts_with('-') && rest.len() > 1 => Some(&rest[1..]),
+            _ => return Err(CalVerError::Mismatch { position: pos, expected: "end of version" }),
+        };
+        if build == Some("") {
+            return Err(CalVerError::Mismatch { position: input.len(), expected: "build" });
+        }
+        v.pre_release = pre_release.map(String::from);
+        v.build = build.map(String::from);
+        Ok(v)
+    }
+}
+
+impl fmt::Display for CalVer {
+    // printed in its own format: "2020.06.07-rc.1"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tokens = tokenize(&self.format).map_err(|_| fmt::Error)?;
+        for token in tokens {
+            match token {
+                Token::FullYear => write!(f, "{:04}", self.year)?,
+                Token::ShortYear => write!(f, "{}", self.year.saturating_sub(2000))?,
+                Token::PaddedYear => write!(f, "{:02}", self.year.saturating_sub(2000))?,
+                Token::Month => write!(f, "{}", self.month.unwrap_or(0))?,
+                Token::PaddedMonth => write!(f, "{:02}", self.month.unwrap_or(0))?,
+                Token::Week => write!(f, "{}", self.week.unwrap_or(0))?,
+                Token::PaddedWeek => write!(f, "{:02}", self.week.unwrap_or(0))?,
+                Token::Day => write!(f, "{}", self.day.unwrap_or(0))?,
+                Token::PaddedDay => write!(f, "{:02}", self.day.unwrap_or(0))?,
+                Token::Major => write!(f, "{}", self.major.unwrap_or(0))?,
+                Token::Minor => write!(f, "{}", self.minor.unwrap_or(0))?,
+                Token::Micro => write!(f, "{}", self.micro.unwrap_or(0))?,
+                Token::Separator(c) => write!(f, "{}", c)?,
+            }
+        }
+        if let Some(pre) = &self.pre_release {
+            write!(f, "-{}", pre)?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{}", build)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for CalVer {
+    // most significant first, the format itself and build metadata are
+    // not part of the precedence: 20.04 (YY.0M) == 2020.4 (YYYY.MM)
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.year, self.month, self.week, self.day, self.major, self.minor, self.micro)
+            .cmp(&(other.year, other.month, other.week, other.day, other.major, other.minor, other.micro))
+            .then_with(|| cmp_pre_release(&self.pre_release, &other.pre_release))
+    }
+}
+
+impl PartialOrd for CalVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for CalVer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for CalVer {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calver_parse() {
+        let v = CalVer::parse("YYYY.0M.0D", "2020.06.07").unwrap();
+        assert_eq!((v.year, v.month, v.day), (2020, Some(6), Some(7)));
+        assert_eq!(v.to_string(), "2020.06.07");
+
+        let v = CalVer::parse("YY.0M.MICRO", "20.04.1-rc.1+b7").unwrap();
+        assert_eq!((v.year, v.month, v.micro), (2020, Some(4), Some(1)));
+        assert_eq!(v.pre_release.as_deref(), Some("rc.1"));
+        assert_eq!(v.build.as_deref(), Some("b7"));
+        assert_eq!(v.to_string(), "20.04.1-rc.1+b7");
+
+        let v = CalVer::parse("YYYY.WW", "2019.52").unwrap();
+        assert_eq!((v.year, v.week), (2019, Some(52)));
+        let v = CalVer::parse("0Y_MM", "106_1").unwrap();
+        assert_eq!((v.year, v.month), (2106, Some(1)));
+    }
+
+    #[test]
+    fn test_calver_parse_errors() {
+        assert_eq!(CalVer::parse("YYYY.QQ", "2020.1"), Err(CalVerError::Format("YYYY.QQ".to_string())));
+        assert_eq!(CalVer::parse("MAJOR.MINOR", "1.2"), Err(CalVerError::Format("MAJOR.MINOR".to_string())));
+        assert_eq!(CalVer::parse("YYYY.0M", "2020.6"),
+                   Err(CalVerError::Mismatch { position: 5, expected: "zero-padded number" }));
+        assert_eq!(CalVer::parse("YYYY.MM", "2020.06"),
+                   Err(CalVerError::Mismatch { position: 5, expected: "number" }));
+        assert_eq!(CalVer::parse("YYYY.MM", "2020.13"),
+                   Err(CalVerError::OutOfRange { position: 5, field: "month" }));
+        assert_eq!(CalVer::parse("YYYY.MM", "2020-1"),
+                   Err(CalVerError::Mismatch { position: 4, expected: "separator" }));
+        assert_eq!(CalVer::parse("YYYY.MM", "2020.1.2"),
+                   Err(CalVerError::Mismatch { position: 6, expected: "end of version" }));
+        // a short year has at most three digits, 2999
+        assert_eq!(CalVer::parse("YY.MM", "999.1").unwrap().year, 2999);
+        assert_eq!(CalVer::parse("YY.MM", "63536.1"), Err(CalVerError::Mismatch { position: 0, expected: "number" }));
+        assert_eq!(CalVer::parse("YY.MM", "100000.1"), Err(CalVerError::Mismatch { position: 0, expected: "number" }));
+    }
+
+    #[test]
+    fn test_calver_ord() {
+        let v = |f, s| CalVer::parse(f, s).unwrap();
+        assert!(v("YYYY.0M.0D", "2020.06.07") < v("YYYY.0M.0D", "2020.06.08"));
+        assert!(v("YYYY.0M.0D", "2020.06.07") < v("YYYY.0M.0D", "2020.10.01"));
+        assert!(v("YYYY.0M.0D", "2020.06.07-rc.1") < v("YYYY.0M.0D", "2020.06.07"));
+        assert!(v("YY.0M.MICRO", "20.04.2") < v("YY.0M.MICRO", "20.10.0"));
+        assert_eq!(v("YY.0M", "20.04"), v("YYYY.MM", "2020.4+ubuntu1"));
+    }
+}
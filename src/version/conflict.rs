@@ -0,0 +1,225 @@
+// Whether the constraints a package collects from several places can hold
+// at once, e.g. its entries in dependencies, peerDependencies and engines:
+//
+//   intersection(&[("dependencies", &lt2), ("peerDependencies", &caret2)])
+//   => Err: `^2.0.0` from peerDependencies conflicts with `<2` from dependencies
+//
+// Works on interval sets, so only semver is understood; queries bounding
+// versions of any other scheme are handed back as skipped rather than
+// guessed at. The intervals leave out node-semver's pre-release rule, a
+// non-empty result is only reported once some version is known to pass it.
+use std::error::Error;
+use std::fmt;
+
+use super::interval::{Interval, IntervalSet};
+use super::range::{Comparator, Op};
+use super::{SemVer, VersionQuery};
+
+// A smallest set of constraints that cannot all hold, in input order, each
+// with where it came from. Dropping any one of them resolves the conflict.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub constraints: Vec<(String, VersionQuery)>,
+}
+
+impl fmt::Display for Conflict {
+    // the constraint that made the intersection empty is named first
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (last, rest) = match self.constraints.split_last() {
+            Some(split) => split,
+            None => return write!(f, "no constraints"),
+        };
+        write!(f, "`{}` from {}", last.1, last.0)?;
+        if rest.is_empty() {
+            return write!(f, " is unsatisfiable");
+        }
+        write!(f, " conflicts with ")?;
+        for (n, (source, query)) in rest.iter().enumerate() {
+            if n > 0 {
+                write!(f, " and ")?;
+            }
+            write!(f, "`{}` from {}", query, source)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for Conflict {}
+
+// What the constraints leave: the versions every semver constraint admits,
+// pre-releases the rule keeps out included, and the constraints that bound
+// another scheme and were not checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Intersection {
+    pub versions: IntervalSet,
+    pub skipped: Vec<(String, VersionQuery)>,
+}
+
+// a semver constraint, desugared once
+struct Checked<'a> {
+    source: &'a str,
+    query: &'a VersionQuery,
+    sets: Vec<Vec<Comparator>>,
+    intervals: IntervalSet,
+}
+
+// The intersection of the constraints, or the conflict that leaves no
+// version satisfying all of them.
+pub fn intersection(constraints: &[(&str, &VersionQuery)]) -> Result<Intersection, Conflict> {
+    let (semver, skipped): (Vec<_>, Vec<_>) = constraints.iter().partition(|(_, q)| is_semver_only(q));
+    let checked: Vec<_> = semver.iter()
+        .map(|(source, q)| {
+            let sets = q.comparator_sets();
+            let intervals = sets.iter()
+                .map(|set| IntervalSet::from_comparators(set))
+                .fold(IntervalSet::empty(), |acc, s| acc.union(&s));
+            Checked { source, query: q, sets, intervals }
+        })
+        .collect();
+    let mut versions = IntervalSet::full();
+    for (i, c) in checked.iter().enumerate() {
+        versions = versions.intersect(&c.intervals);
+        let prefix: Vec<_> = checked[..=i].iter().collect();
+        if !admits_any(&prefix) {
+            return Err(Conflict { constraints: minimal_core(prefix) });
+        }
+    }
+    let skipped = skipped.iter().map(|(source, q)| (source.to_string(), (*q).clone())).collect();
+    Ok(Intersection { versions, skipped })
+}
+
+// None when the semver constraints agree but others had to be skipped
+pub fn is_satisfiable(constraints: &[(&str, &VersionQuery)]) -> Option<bool> {
+    match intersection(constraints) {
+        Ok(i) if i.skipped.is_empty() => Some(true),
+        Ok(_) => None,
+        Err(_) => Some(false),
+    }
+}
+
+// Drops constraints, first to last, for as long as the rest still conflict.
+// The last one is kept: it is the one that emptied the intersection.
+fn minimal_core(mut core: Vec<&Checked>) -> Vec<(String, VersionQuery)> {
+    let mut i = 0;
+    while i + 1 < core.len() {
+        let without: Vec<_> = core.iter().enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, c)| *c)
+            .collect();
+        if !admits_any(&without) {
+            core.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    core.into_iter().map(|c| (c.source.to_string(), c.query.clone())).collect()
+}
+
+// Whether some version satisfies every constraint. An interval without a
+// release in it holds only pre-releases of one [major, minor, patch] tuple,
+// which a constraint admits through a comparator set that opts in to
+// pre-releases of that tuple.
+fn admits_any(constraints: &[&Checked]) -> bool {
+    let versions = constraints.iter().fold(IntervalSet::full(), |acc, c| acc.intersect(&c.intervals));
+    versions.intervals().iter().any(|i| {
+        let release = lowest_release(i);
+        if i.contains(&release) {
+            return true;
+        }
+        let mut lowest = release.clone();
+        lowest.pre_release = Some("0".to_string());
+        let pre_releases = IntervalSet::from_comparators(&[
+            Comparator::new(Op::Gte, lowest),
+            Comparator::new(Op::Lt, release.clone()),
+        ]);
+        let admitted = constraints.iter().fold(versions.intersect(&pre_releases), |acc, c| {
+            let opted_in = c.sets.iter()
+                .filter(|set| set.iter().any(|cmp| opts_in(cmp, &release)))
+                .fold(IntervalSet::empty(), |acc, set| acc.union(&IntervalSet::from_comparators(set)));
+            acc.intersect(&opted_in)
+        });
+        !admitted.is_empty()
+    })
+}
+
+// the lowest release at or above the lower bound of i
+fn lowest_release(i: &Interval) -> SemVer {
+    match &i.lower {
+        Some(lower) => SemVer { pre_release: None, build: None, ..lower.clone() },
+        None => SemVer::new(0, 0, 0),
+    }
+}
+
+fn opts_in(c: &Comparator, release: &SemVer) -> bool {
+    let v = &c.version;
+    v.pre_release.is_some() && (v.major, v.minor, v.patch) == (release.major, release.minor, release.patch)
+}
+
+fn is_semver_only(q: &VersionQuery) -> bool {
+    use VersionQuery::*;
+    match q {
+        And(lhs, rhs) | Or(lhs, rhs) | Range(lhs, rhs) => is_semver_only(lhs) && is_semver_only(rhs),
+        Not(q) => is_semver_only(q),
+        Lt(v) | Lte(v) | Gt(v) | Gte(v) | Eq(v) | Approx(v) | Compat(v) | Version(v) => {
+            matches!(v, super::Version::SemVer(_) | super::Version::Missing)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::npm_semver::parse_range;
+    use crate::version::Version;
+
+    fn check(constraints: &[(&str, &str)]) -> Result<String, String> {
+        let queries: Vec<_> = constraints.iter().map(|(_, r)| parse_range(r).unwrap()).collect();
+        let pairs: Vec<_> = constraints.iter().zip(queries.iter()).map(|((s, _), q)| (*s, q)).collect();
+        intersection(&pairs).map(|i| i.versions.to_string()).map_err(|c| c.to_string())
+    }
+
+    #[test]
+    fn test_intersection() {
+        assert_eq!(check(&[("dependencies", "^1.2.3"), ("peerDependencies", "~1.4")]),
+                   Ok("[1.4.0, 1.5.0-0)".to_string()));
+        assert_eq!(check(&[]), Ok("(-inf, +inf)".to_string()));
+        assert_eq!(check(&[("dependencies", "<2"), ("peerDependencies", "^2.0.0")]),
+                   Err("`^2.0.0` from peerDependencies conflicts with `<2` from dependencies".to_string()));
+        assert_eq!(check(&[("dependencies", ">2 <1")]),
+                   Err("`>2 <1` from dependencies is unsatisfiable".to_string()));
+    }
+
+    #[test]
+    fn test_conflict_is_minimal() {
+        // engines has nothing to do with it
+        assert_eq!(check(&[("engines", ">=1"), ("dependencies", "<2"), ("peerDependencies", "^2.0.0")]),
+                   Err("`^2.0.0` from peerDependencies conflicts with `<2` from dependencies".to_string()));
+        // no two of them conflict, all three do
+        let three = [("dependencies", "1.x || 2.x"), ("devDependencies", "2.x || 3.x"), ("peerDependencies", "1.x || 3.x")];
+        assert_eq!(check(&three), Err("`1.x || 3.x` from peerDependencies conflicts with \
+                   `1.x || 2.x` from dependencies and `2.x || 3.x` from devDependencies".to_string()));
+    }
+
+    #[test]
+    fn test_pre_release_rule() {
+        // only 1.2.2 pre-releases get past the second range
+        assert_eq!(check(&[("dependencies", "^1.2.3-beta"), ("peerDependencies", "<1.2.3 >=1.2.2-alpha")]),
+                   Err("`<1.2.3 >=1.2.2-alpha` from peerDependencies conflicts with `^1.2.3-beta` from dependencies".to_string()));
+        assert_eq!(check(&[("dependencies", "^1.2.3-beta"), ("peerDependencies", "<1.2.3 >=1.2.3-alpha")]),
+                   Ok("[1.2.3-beta, 1.2.3)".to_string()));
+        assert!(check(&[("dependencies", ">=1.2.3-beta <1.2.3"), ("engines", ">=1.0.0")]).is_err());
+        assert!(check(&[("dependencies", ">=1.2.3-beta <1.2.4"), ("engines", ">=1.0.0")]).is_ok());
+    }
+
+    #[test]
+    fn test_other_schemes_are_skipped() {
+        let numver = VersionQuery::Lt(Version::classify("1.0.0.1"));
+        let semver = parse_range("^2").unwrap();
+        let constraints = [("dependencies", &numver), ("peerDependencies", &semver)];
+        let i = intersection(&constraints).unwrap();
+        assert_eq!(i.skipped, [("dependencies".to_string(), numver.clone())]);
+        assert_eq!(i.versions.to_string(), "[2.0.0, 3.0.0-0)");
+        assert_eq!(is_satisfiable(&constraints), None);
+        assert_eq!(is_satisfiable(&constraints[1..]), Some(true));
+    }
+}
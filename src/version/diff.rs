@@ -0,0 +1,201 @@
+// What kind of change going from one version to another is:
+//   1.2.3 -> 1.3.0       Minor
+//   0.1.2 -> 0.2.0       Major, 0.y.z is compatible only within y
+//   1.2.3 -> 1.2.3+b7    Build
+//   2020.06.07 -> 2020.06.08  Date
+//
+// Bumps are judged the way ^ reads versions, relative to the leftmost
+// non-zero number of the old version, so for 0.y.z a change of y is Major
+// and a change of z is Minor, and for 0.0.z every change is Major.
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::{CalVer, NumVer, SemVer, SemVerField, Version};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionDiff {
+    Same,
+    Build,      // only build metadata differs
+    PreRelease, // same MAJOR.MINOR.PATCH, different pre-release
+    Patch,
+    Minor,
+    Major,
+    Date,       // a CalVer's date moved on, which promises nothing either way
+    Downgrade,
+    Unknown, // different schemes, partial versions or a StrVer change
+}
+
+impl VersionDiff {
+    // Whether the change may break dependents: major bumps, downgrades,
+    // pre-releases (which promise nothing) and changes we cannot judge.
+    pub fn is_breaking(self) -> bool {
+        use VersionDiff::*;
+        matches!(self, PreRelease | Major | Downgrade | Unknown)
+    }
+}
+
+impl fmt::Display for VersionDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            VersionDiff::Same => "same",
+            VersionDiff::Build => "build",
+            VersionDiff::PreRelease => "pre-release",
+            VersionDiff::Patch => "patch",
+            VersionDiff::Minor => "minor",
+            VersionDiff::Major => "major",
+            VersionDiff::Date => "date",
+            VersionDiff::Downgrade => "downgrade",
+            VersionDiff::Unknown => "unknown",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl SemVer {
+    pub fn diff(&self, to: &SemVer) -> VersionDiff {
+        if !self.is_complete() || !to.is_complete() {
+            return VersionDiff::Unknown;
+        }
+        match to.cmp(self) {
+            Ordering::Less => return VersionDiff::Downgrade,
+            Ordering::Equal if self.build == to.build => return VersionDiff::Same,
+            Ordering::Equal => return VersionDiff::Build,
+            Ordering::Greater => {}
+        }
+        let from = [self.major, self.minor, self.patch];
+        let to = [to.major, to.minor, to.patch];
+        match from.iter().zip(to.iter()).position(|(a, b)| a != b) {
+            Some(changed) => {
+                let compat = from.iter().position(|f| *f != SemVerField::Number(0));
+                bump(changed, compat.unwrap_or(2))
+            }
+            None => VersionDiff::PreRelease,
+        }
+    }
+}
+
+impl CalVer {
+    // The first field to change in order of precedence decides: the date,
+    // then MAJOR, MINOR and MICRO where the format has them.
+    pub fn diff(&self, to: &CalVer) -> VersionDiff {
+        match to.cmp(self) {
+            Ordering::Less => return VersionDiff::Downgrade,
+            Ordering::Equal if self.build == to.build => return VersionDiff::Same,
+            Ordering::Equal => return VersionDiff::Build,
+            Ordering::Greater => {}
+        }
+        let date = |v: &CalVer| (v.year, v.month, v.week, v.day);
+        if date(self) != date(to) {
+            VersionDiff::Date
+        } else if self.major != to.major {
+            VersionDiff::Major
+        } else if self.minor != to.minor {
+            VersionDiff::Minor
+        } else if self.micro != to.micro {
+            VersionDiff::Patch
+        } else {
+            VersionDiff::PreRelease
+        }
+    }
+}
+
+impl Version {
+    pub fn diff(&self, to: &Version) -> VersionDiff {
+        match (self, to) {
+            (Version::SemVer(a), Version::SemVer(b)) => a.diff(b),
+            (Version::CalVer(a), Version::CalVer(b)) => a.diff(b),
+            (Version::NumVer(a), Version::NumVer(b)) => match b.cmp(a) {
+                Ordering::Less => VersionDiff::Downgrade,
+                Ordering::Equal => VersionDiff::Same,
+                Ordering::Greater => {
+                    // read like SemVer, missing trailing numbers are zeros
+                    let changed = (0..).find(|&i| digits(a, i) != digits(b, i)).unwrap();
+                    let compat = (0..a.numbers.len()).find(|&i| !digits(a, i).is_empty());
+                    bump(changed, compat.unwrap_or(a.numbers.len() - 1))
+                }
+            },
+            (Version::StrVer(a), Version::StrVer(b)) if a == b => VersionDiff::Same,
+            (Version::Missing, Version::Missing) => VersionDiff::Same,
+            _ => VersionDiff::Unknown,
+        }
+    }
+}
+
+// the i-th number without leading zeros, "" for zero or missing
+fn digits(v: &NumVer, i: usize) -> &str {
+    v.numbers.get(i).map_or("", |n| n.trim_start_matches('0'))
+}
+
+// changed and compat index the version's numbers, compat being the leftmost
+// non-zero one of the old version
+fn bump(changed: usize, compat: usize) -> VersionDiff {
+    if changed <= compat {
+        VersionDiff::Major
+    } else if changed == compat + 1 {
+        VersionDiff::Minor
+    } else {
+        VersionDiff::Patch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use VersionDiff::*;
+
+    fn diff(from: &str, to: &str) -> VersionDiff {
+        Version::classify(from).diff(&Version::classify(to))
+    }
+
+    #[test]
+    fn test_semver_diff() {
+        assert_eq!(diff("1.2.3", "1.2.3"), Same);
+        assert_eq!(diff("1.2.3+b1", "1.2.3+b2"), Build);
+        assert_eq!(diff("1.2.3-rc.1", "1.2.3-rc.2"), PreRelease);
+        assert_eq!(diff("1.2.3-rc.1", "1.2.3"), PreRelease);
+        assert_eq!(diff("1.2.3", "1.2.4"), Patch);
+        assert_eq!(diff("1.2.3", "1.2.4-beta"), Patch);
+        assert_eq!(diff("1.2.3", "1.3.0"), Minor);
+        assert_eq!(diff("1.2.3", "2.0.0"), Major);
+        assert_eq!(diff("1.2.3", "1.2.2"), Downgrade);
+        assert_eq!(diff("1.2.3", "1.2.3-rc.1"), Downgrade);
+    }
+
+    #[test]
+    fn test_semver_diff_zero_major() {
+        assert_eq!(diff("0.1.2", "0.1.3"), Minor);
+        assert_eq!(diff("0.1.2", "0.2.0"), Major);
+        assert_eq!(diff("0.1.2", "1.0.0"), Major);
+        assert_eq!(diff("0.0.1", "0.0.2"), Major);
+        assert_eq!(diff("0.0.0", "0.0.1"), Major);
+        assert!(diff("0.1.2", "0.2.0").is_breaking());
+        assert!(!diff("0.1.2", "0.1.3").is_breaking());
+    }
+
+    #[test]
+    fn test_other_scheme_diff() {
+        assert_eq!(diff("1.2.3.4", "1.2.3.5"), Patch);
+        assert_eq!(diff("1.2.3.4", "1.3"), Minor);
+        assert_eq!(diff("1.02", "1.2"), Same);
+        assert_eq!(diff("0.9.1.0", "0.10"), Major);
+        assert_eq!(diff("2020.06.07", "2020.06.08"), Date);
+        assert!(!diff("2020.06.07", "2020.06.08").is_breaking());
+        assert_eq!(diff("2020.06.07", "2020.06.01"), Downgrade);
+        assert_eq!(diff("latest", "latest"), Same);
+        assert_eq!(diff("1.2.3", "2020.06.07"), Unknown);
+        let partial = Version::SemVer(SemVer { minor: SemVerField::Wildcard, ..SemVer::new(1, 0, 0) });
+        assert_eq!(partial.diff(&Version::classify("1.2.0")), Unknown);
+        assert_eq!(Major.to_string(), "major");
+    }
+
+    #[test]
+    fn test_calver_diff() {
+        let v = |format, s| Version::CalVer(CalVer::parse(format, s).unwrap());
+        assert_eq!(v("YY.0M.MICRO", "20.04.1").diff(&v("YY.0M.MICRO", "20.04.2")), Patch);
+        assert_eq!(v("YY.0M.MICRO", "20.04.1").diff(&v("YY.0M.MICRO", "20.05.0")), Date);
+        assert_eq!(v("YYYY.MAJOR.MINOR", "2020.1.4").diff(&v("YYYY.MAJOR.MINOR", "2020.2.0")), Major);
+        assert_eq!(v("YYYY.MAJOR.MINOR", "2020.1.4").diff(&v("YYYY.MAJOR.MINOR", "2020.1.5")), Minor);
+        assert_eq!(v("YY.0M.MICRO", "20.04.1-rc.1").diff(&v("YY.0M.MICRO", "20.04.1")), PreRelease);
+        assert_eq!(Date.to_string(), "date");
+    }
+}
@@ -0,0 +1,174 @@
+// Next versions the way node-semver's inc() computes them
+// https://github.com/npm/node-semver#functions
+//
+//   1.2.3       prepatch "rc"    => 1.2.4-rc.0
+//   1.2.4-rc.0  prerelease "rc"  => 1.2.4-rc.1
+//   1.2.4-rc.1  patch            => 1.2.4
+//
+// Build metadata is dropped, as in node.
+use super::{next_number, SemVer, SemVerField};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReleaseType {
+    Major,
+    Minor,
+    Patch,
+    PreMajor,
+    PreMinor,
+    PrePatch,
+    PreRelease,
+}
+
+impl SemVer {
+    // None for partial versions, a field that would overflow or a preid that
+    // is not a valid pre-release
+    pub fn inc(&self, release: ReleaseType, preid: Option<&str>) -> Option<SemVer> {
+        if !self.is_complete() || !preid.is_none_or(is_valid_pre_release) {
+            return None;
+        }
+        let mut v = self.clone();
+        v.build = None;
+        match release {
+            // releasing a pre-release of the very version it precedes only
+            // drops the pre-release: 2.0.0-rc.1 => 2.0.0
+            ReleaseType::Major => {
+                if v.pre_release.is_none() || v.minor != zero() || v.patch != zero() {
+                    v.major = inc_field(v.major)?;
+                    v.minor = zero();
+                    v.patch = zero();
+                }
+                v.pre_release = None;
+            }
+            ReleaseType::Minor => {
+                if v.pre_release.is_none() || v.patch != zero() {
+                    v.minor = inc_field(v.minor)?;
+                    v.patch = zero();
+                }
+                v.pre_release = None;
+            }
+            ReleaseType::Patch => {
+                if v.pre_release.is_none() {
+                    v.patch = inc_field(v.patch)?;
+                }
+                v.pre_release = None;
+            }
+            ReleaseType::PreMajor => {
+                v.major = inc_field(v.major)?;
+                v.minor = zero();
+                v.patch = zero();
+                v.pre_release = Some(inc_pre_release(None, preid)?);
+            }
+            ReleaseType::PreMinor => {
+                v.minor = inc_field(v.minor)?;
+                v.patch = zero();
+                v.pre_release = Some(inc_pre_release(None, preid)?);
+            }
+            ReleaseType::PrePatch => {
+                v.patch = inc_field(v.patch)?;
+                v.pre_release = Some(inc_pre_release(None, preid)?);
+            }
+            ReleaseType::PreRelease => {
+                if v.pre_release.is_none() {
+                    v.patch = inc_field(v.patch)?;
+                }
+                v.pre_release = Some(inc_pre_release(v.pre_release.as_deref(), preid)?);
+            }
+        }
+        Some(v)
+    }
+}
+
+fn zero() -> SemVerField {
+    SemVerField::Number(0)
+}
+
+fn inc_field(field: SemVerField) -> Option<SemVerField> {
+    match field {
+        SemVerField::Number(n) => next_number(n).map(SemVerField::Number),
+        _ => None,
+    }
+}
+
+// Bumps the last numeric identifier, appending ".0" when there is none:
+//   rc.1 => rc.2, rc => rc.0, None => 0
+// A preid that is not already the leading identifier starts over at preid.0.
+fn inc_pre_release(pre: Option<&str>, preid: Option<&str>) -> Option<String> {
+    let mut ids: Vec<String> = pre.map_or(vec![], |p| p.split('.').map(String::from).collect());
+    match ids.iter().rposition(|id| id.bytes().all(|c| c.is_ascii_digit())) {
+        Some(i) => ids[i] = (ids[i].parse::<u64>().ok()?.checked_add(1)?).to_string(),
+        None => ids.push("0".to_string()),
+    }
+    if let Some(preid) = preid {
+        let keeps_number = ids.len() > 1 && ids[1].bytes().all(|c| c.is_ascii_digit());
+        if !(ids[0] == preid && keeps_number) {
+            ids = vec![preid.to_string(), "0".to_string()];
+        }
+    }
+    Some(ids.join("."))
+}
+
+fn is_valid_pre_release(pre: &str) -> bool {
+    pre.split('.').all(|id| {
+        let numeric = id.bytes().all(|c| c.is_ascii_digit());
+        !id.is_empty()
+            && id.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-')
+            && !(numeric && id.len() > 1 && id.starts_with('0'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ReleaseType::*;
+
+    fn inc(v: &str, release: ReleaseType, preid: Option<&str>) -> Option<String> {
+        v.parse::<SemVer>().unwrap().inc(release, preid).map(|v| v.to_string())
+    }
+
+    #[test]
+    fn test_inc() {
+        // from node-semver's test/fixtures/increments.js
+        let cases = [
+            ("1.2.3", Major, None, "2.0.0"),
+            ("1.2.3", Minor, None, "1.3.0"),
+            ("1.2.3", Patch, None, "1.2.4"),
+            ("1.2.3-tag", Major, None, "2.0.0"),
+            ("1.0.0-1", Major, None, "1.0.0"),
+            ("1.2.0-1", Minor, None, "1.2.0"),
+            ("1.2.3-1", Minor, None, "1.3.0"),
+            ("1.2.3-tag", Patch, None, "1.2.3"),
+            ("1.2.3+build", Patch, None, "1.2.4"),
+            ("1.2.3", PreRelease, None, "1.2.4-0"),
+            ("1.2.3-0", PreRelease, None, "1.2.3-1"),
+            ("1.2.3-alpha.0", PreRelease, None, "1.2.3-alpha.1"),
+            ("1.2.3-alpha.1", PreRelease, None, "1.2.3-alpha.2"),
+            ("1.2.3-alpha.0.beta", PreRelease, None, "1.2.3-alpha.1.beta"),
+            ("1.2.3-alpha.beta", PreRelease, None, "1.2.3-alpha.beta.0"),
+            ("1.2.0", PrePatch, None, "1.2.1-0"),
+            ("1.2.0-1", PrePatch, None, "1.2.1-0"),
+            ("1.2.0", PreMinor, None, "1.3.0-0"),
+            ("1.2.3-1", PreMinor, None, "1.3.0-0"),
+            ("1.2.0", PreMajor, None, "2.0.0-0"),
+            ("1.2.3-1", PreMajor, None, "2.0.0-0"),
+            ("1.2.3", PrePatch, Some("rc"), "1.2.4-rc.0"),
+            ("1.2.4-rc.0", PreRelease, Some("rc"), "1.2.4-rc.1"),
+            ("1.2.4-rc", PreRelease, Some("rc"), "1.2.4-rc.0"),
+            ("1.2.4-beta.3", PreRelease, Some("rc"), "1.2.4-rc.0"),
+            ("1.2.3", PreMajor, Some("dev"), "2.0.0-dev.0"),
+            ("1.2.3", PreMinor, Some("dev"), "1.3.0-dev.0"),
+        ];
+        for (v, release, preid, expected) in cases.iter() {
+            assert_eq!(inc(v, *release, *preid).as_deref(), Some(*expected), "{} {:?} {:?}", v, release, preid);
+        }
+    }
+
+    #[test]
+    fn test_inc_invalid() {
+        assert_eq!(inc("1.2.3", PreRelease, Some("01")), None);
+        assert_eq!(inc("1.2.3", PreRelease, Some("rc..1")), None);
+        assert_eq!(inc("1.2.9007199254740991", Patch, None), None);
+        assert_eq!(inc("1.2.3-18446744073709551615", PreRelease, None), None);
+        let partial = SemVer { patch: SemVerField::Wildcard, ..SemVer::new(1, 2, 0) };
+        assert_eq!(partial.inc(Minor, None), None);
+    }
+}
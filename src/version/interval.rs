@@ -0,0 +1,309 @@
+// Canonical form of a VersionQuery as a union of disjoint half-open
+// intervals over SemVer precedence:
+//   "^1.2.3 <1.5 || 1.4.x" => [1.2.3, 1.5.0-0)
+//
+// Intervals describe exactly the versions the desugared comparators admit,
+// node-semver's pre-release exclusion rule (see range::test_set) is not
+// modelled, so e.g. 1.5.0-beta counts as part of [1.2.3, 2.0.0-0).
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::range::{Comparator, Op};
+use super::{next_number, SemVer, VersionQuery};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval {
+    pub lower: Option<SemVer>, // inclusive, None is unbounded
+    pub upper: Option<SemVer>, // exclusive, None is unbounded
+}
+
+// Sorted, pairwise disjoint and non-adjacent intervals, so two sets that
+// admit the same versions are always equal.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl Interval {
+    pub fn contains(&self, v: &SemVer) -> bool {
+        self.lower.as_ref().is_none_or(|l| v >= l)
+            && self.upper.as_ref().is_none_or(|u| v < u)
+    }
+
+    fn is_empty(&self) -> bool {
+        match (&self.lower, &self.upper) {
+            (Some(l), Some(u)) => l >= u,
+            _ => false,
+        }
+    }
+
+    fn from_comparator(c: &Comparator) -> Self {
+        let v = bound(&c.version);
+        let (lower, upper) = match c.op {
+            Op::Lt => (None, Some(v)),
+            Op::Lte => (None, successor(&v)),
+            Op::Gt => match successor(&v) {
+                Some(s) => (Some(s), None),
+                None => (Some(v.clone()), Some(v)), // nothing above the maximum
+            },
+            Op::Gte => (Some(v), None),
+            Op::Eq => (Some(v.clone()), successor(&v)),
+        };
+        Interval { lower: normalize_lower(lower), upper }
+    }
+
+    fn intersect(&self, other: &Self) -> Self {
+        let lower = match (&self.lower, &other.lower) {
+            (Some(a), Some(b)) => Some(a.max(b).clone()),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+        let upper = match (&self.upper, &other.upper) {
+            (Some(a), Some(b)) => Some(a.min(b).clone()),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+        Interval { lower, upper }
+    }
+}
+
+impl IntervalSet {
+    pub fn empty() -> Self {
+        IntervalSet { intervals: vec![] }
+    }
+
+    pub fn full() -> Self {
+        IntervalSet { intervals: vec![Interval { lower: None, upper: None }] }
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        *self == IntervalSet::full()
+    }
+
+    pub fn contains(&self, v: &SemVer) -> bool {
+        self.intervals.iter().any(|i| i.contains(v))
+    }
+
+    pub fn from_comparators(set: &[Comparator]) -> Self {
+        let all = Interval { lower: None, upper: None };
+        let interval = set.iter()
+            .map(Interval::from_comparator)
+            .fold(all, |acc, i| acc.intersect(&i));
+        IntervalSet::from_intervals(vec![interval])
+    }
+
+    fn from_intervals(mut intervals: Vec<Interval>) -> Self {
+        intervals.retain(|i| !i.is_empty());
+        intervals.sort_by(|a, b| cmp_lower(&a.lower, &b.lower));
+        let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for i in intervals {
+            if let Some(last) = merged.last_mut() {
+                // [a, b) and [c, d) overlap or touch when c <= b
+                let touches = match (&last.upper, &i.lower) {
+                    (None, _) | (_, None) => true,
+                    (Some(b), Some(c)) => c <= b,
+                };
+                if touches {
+                    if cmp_upper(&i.upper, &last.upper) == Ordering::Greater {
+                        last.upper = i.upper;
+                    }
+                    continue;
+                }
+            }
+            merged.push(i);
+        }
+        IntervalSet { intervals: merged }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let all = self.intervals.iter().chain(other.intervals.iter()).cloned().collect();
+        IntervalSet::from_intervals(all)
+    }
+
+    pub fn intersect(&self, other: &Self) -> Self {
+        let mut intervals = vec![];
+        for a in self.intervals.iter() {
+            for b in other.intervals.iter() {
+                intervals.push(a.intersect(b));
+            }
+        }
+        IntervalSet::from_intervals(intervals)
+    }
+
+    pub fn complement(&self) -> Self {
+        let mut gaps = vec![];
+        let mut from = None; // start of the current gap
+        for i in self.intervals.iter() {
+            if let Some(l) = &i.lower {
+                gaps.push(Interval { lower: from.clone(), upper: Some(l.clone()) });
+            }
+            match &i.upper {
+                Some(u) => from = Some(u.clone()),
+                None => return IntervalSet::from_intervals(gaps),
+            }
+        }
+        gaps.push(Interval { lower: from, upper: None });
+        IntervalSet::from_intervals(gaps)
+    }
+
+    // every version admitted by self is admitted by other
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.intersect(&other.complement()).is_empty()
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersect(other).is_empty()
+    }
+}
+
+impl VersionQuery {
+    pub fn to_intervals(&self) -> IntervalSet {
+        self.comparator_sets().iter()
+            .map(|set| IntervalSet::from_comparators(set))
+            .fold(IntervalSet::empty(), |acc, s| acc.union(&s))
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.lower {
+            Some(l) => write!(f, "[{}, ", l)?,
+            None => write!(f, "(-inf, ")?,
+        }
+        match &self.upper {
+            Some(u) => write!(f, "{})", u),
+            None => write!(f, "+inf)"),
+        }
+    }
+}
+
+impl fmt::Display for IntervalSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.intervals.is_empty() {
+            return write!(f, "{{}}");
+        }
+        for (n, i) in self.intervals.iter().enumerate() {
+            if n > 0 {
+                write!(f, " U ")?;
+            }
+            write!(f, "{}", i)?;
+        }
+        Ok(())
+    }
+}
+
+// None sorts first as a lower bound and last as an upper bound
+fn cmp_lower(a: &Option<SemVer>, b: &Option<SemVer>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(a), Some(b)) => a.cmp(b),
+    }
+}
+
+fn cmp_upper(a: &Option<SemVer>, b: &Option<SemVer>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => a.cmp(b),
+    }
+}
+
+// versions without build metadata, which has no precedence
+fn bound(v: &SemVer) -> SemVer {
+    let mut v = v.clone();
+    v.build = None;
+    v
+}
+
+// 0.0.0-0 is the lowest version there is, so >=0.0.0-0 is unbounded
+fn normalize_lower(lower: Option<SemVer>) -> Option<SemVer> {
+    let mut min = SemVer::new(0, 0, 0);
+    min.pre_release = Some("0".to_string());
+    lower.filter(|l| *l > min)
+}
+
+// The smallest version with higher precedence than v:
+//   1.2.3 => 1.2.4-0, 1.2.3-alpha => 1.2.3-alpha.0
+// None when v is the highest representable release.
+pub(crate) fn successor(v: &SemVer) -> Option<SemVer> {
+    use super::SemVerField::Number;
+    let mut next = bound(v);
+    if let Some(pre) = &v.pre_release {
+        next.pre_release = Some(format!("{}.0", pre));
+        return Some(next);
+    }
+    next.pre_release = Some("0".to_string());
+    match (v.major, v.minor, v.patch) {
+        (Number(ma), Number(mi), Number(pa)) => {
+            if let Some(pa) = next_number(pa) {
+                next.patch = Number(pa);
+            } else if let Some(mi) = next_number(mi) {
+                next.minor = Number(mi);
+                next.patch = Number(0);
+            } else {
+                next.major = Number(next_number(ma)?);
+                next.minor = Number(0);
+                next.patch = Number(0);
+            }
+            Some(next)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::MAX_NUMBER;
+    use crate::version::npm_semver::parse_range;
+
+    fn intervals(range: &str) -> String {
+        parse_range(range).unwrap().to_intervals().to_string()
+    }
+
+    #[test]
+    fn test_to_intervals() {
+        assert_eq!(intervals("^1.2.3 <1.5 || 1.4.x"), "[1.2.3, 1.5.0-0)");
+        assert_eq!(intervals("*"), "(-inf, +inf)");
+        assert_eq!(intervals(">=0.0.0-0"), "(-inf, +inf)");
+        assert_eq!(intervals("1.2.3"), "[1.2.3, 1.2.4-0)");
+        assert_eq!(intervals("<=1.2.3-beta"), "(-inf, 1.2.3-beta.0)");
+        assert_eq!(intervals(">1.2.3"), "[1.2.4-0, +inf)");
+        assert_eq!(intervals("^1.2.3 || ^3"), "[1.2.3, 2.0.0-0) U [3.0.0, 4.0.0-0)");
+        assert_eq!(intervals("1.x || 2.x"), "[1.0.0, 2.0.0-0) U [2.0.0, 3.0.0-0)");
+        assert_eq!(intervals("1.x || >=2.0.0-0 <3"), "[1.0.0, 3.0.0-0)");
+        assert_eq!(intervals(">2 <1"), "{}");
+        assert_eq!(intervals("<*"), "{}");
+        assert_eq!(intervals("1.2.3+build"), intervals("1.2.3"));
+    }
+
+    #[test]
+    fn test_interval_set_algebra() {
+        let set = |r| parse_range(r).unwrap().to_intervals();
+        assert_eq!(set("^1.2.3").complement().to_string(), "(-inf, 1.2.3) U [2.0.0-0, +inf)");
+        assert_eq!(set("*").complement(), IntervalSet::empty());
+        assert_eq!(IntervalSet::empty().complement(), IntervalSet::full());
+        assert_eq!(set("^1.2.3").intersect(&set("~1.4")), set("~1.4"));
+        assert_eq!(set("^1.2.3").union(&set(">=1.9 <2.1")), set(">=1.2.3 <2.1"));
+        assert!(set("~1.4.2").is_subset(&set("^1.2.3")));
+        assert!(!set("^1.2.3").is_subset(&set("~1.4.2")));
+        assert!(set("^1").is_disjoint(&set("^2")));
+        assert!(!set("^1").is_disjoint(&set(">=1.9 <2.1")));
+    }
+
+    #[test]
+    fn test_successor() {
+        let v = SemVer::new(1, MAX_NUMBER, MAX_NUMBER);
+        assert_eq!(successor(&v).unwrap().to_string(), "2.0.0-0");
+        assert_eq!(successor(&SemVer::new(MAX_NUMBER, MAX_NUMBER, MAX_NUMBER)), None);
+    }
+}
@@ -0,0 +1,595 @@
+pub mod calver;
+pub mod conflict;
+pub mod diff;
+pub mod inc;
+pub mod interval;
+pub mod npm_semver;
+pub mod range;
+pub mod render;
+pub mod serialize;
+pub mod simplify;
+pub mod translate;
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use pest::Parser;
+
+// The package ecosystems whose range syntax we understand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ecosystem {
+    Npm,
+    Cargo,
+    Pep440,
+    Maven,
+    RubyGems,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VersionQuery {
+    And(Box<Self>, Box<Self>),   // >1.2.3 <2.0
+    Or(Box<Self>, Box<Self>),    // >1.0.0 || >=2.3.1
+    Range(Box<Self>, Box<Self>), // 1.2.3 - 1.9
+    Not(Box<Self>),              // !1.2.5
+    Lt(Version),     // <1.2.3
+    Lte(Version),    // <=1.2.3
+    Gt(Version),     // >1.2.3
+    Gte(Version),    // >=1.2.3
+    Eq(Version),     // =1.2.3
+    Approx(Version), // ~1.2.3
+    Compat(Version), // ^1.2.3
+    Version(Version), // 1.2.3
+}
+
+// Equality and ordering follow semver.org precedence, so build metadata is
+// ignored: 1.0.0+001 == 1.0.0+002.
+#[derive(Debug, Clone)]
+pub struct SemVer {
+    // https://semver.org/
+    pub major: SemVerField,
+    pub minor: SemVerField,
+    pub patch: SemVerField,
+    pub pre_release: Option<String>,
+    pub build: Option<String>,
+}
+
+// Partial versions order as Missing < Number(_) < Wildcard, i.e. "1" sorts
+// before every "1.y" and "1.x" sorts after every "1.y".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemVerField {
+    Number(u64),
+    Wildcard,
+    Missing,
+}
+
+// Versions of different schemes are not comparable in any meaningful way,
+// they sort by scheme (Missing < SemVer < CalVer < NumVer < StrVer) so a
+// total order exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Version {
+    SemVer(SemVer),
+    CalVer(CalVer),
+    NumVer(NumVer),
+    StrVer(StrVer),
+    Missing,
+}
+
+// node-semver's ceiling for major, minor and patch, Number.MAX_SAFE_INTEGER
+pub const MAX_NUMBER: u64 = 9_007_199_254_740_991;
+
+// "30001157", but not past MAX_NUMBER; leading zeros are left to the caller
+pub fn parse_number(s: &str) -> Option<u64> {
+    s.parse().ok().filter(|n| *n <= MAX_NUMBER)
+}
+
+// The number after n, None past MAX_NUMBER
+pub fn next_number(n: u64) -> Option<u64> {
+    n.checked_add(1).filter(|n| *n <= MAX_NUMBER)
+}
+
+impl SemVer {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        SemVer {
+            major: SemVerField::Number(major),
+            minor: SemVerField::Number(minor),
+            patch: SemVerField::Number(patch),
+            pre_release: None,
+            build: None,
+        }
+    }
+
+    // true when major, minor and patch are all numbers, e.g. not "1.x"
+    pub fn is_complete(&self) -> bool {
+        [self.major, self.minor, self.patch].iter()
+            .all(|f| matches!(f, SemVerField::Number(_)))
+    }
+}
+
+impl fmt::Display for SemVerField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SemVerField::Number(n) => write!(f, "{}", n),
+            SemVerField::Wildcard => write!(f, "x"),
+            SemVerField::Missing => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for SemVer {
+    // missing fields are left out: "1.2", "1.x", "1.2.3-alpha+001"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.major)?;
+        for field in [self.minor, self.patch].iter() {
+            if *field != SemVerField::Missing {
+                write!(f, ".{}", field)?;
+            }
+        }
+        if let Some(pre) = &self.pre_release {
+            write!(f, "-{}", pre)?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{}", build)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for SemVerField {
+    fn cmp(&self, other: &Self) -> Ordering {
+        use SemVerField::*;
+        match (self, other) {
+            (Number(a), Number(b)) => a.cmp(b),
+            (Missing, Missing) | (Wildcard, Wildcard) => Ordering::Equal,
+            (Missing, _) | (_, Wildcard) => Ordering::Less,
+            (_, Missing) | (Wildcard, _) => Ordering::Greater,
+        }
+    }
+}
+
+impl PartialOrd for SemVerField {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SemVer {
+    // https://semver.org/#spec-item-11
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.major.cmp(&other.major)
+            .then_with(|| self.minor.cmp(&other.minor))
+            .then_with(|| self.patch.cmp(&other.patch))
+            .then_with(|| cmp_pre_release(&self.pre_release, &other.pre_release))
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SemVer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SemVer {}
+
+// calendar formats tried by Version::classify, most specific first
+const CALVER_FORMATS: [&str; 4] = ["YYYY.0M.0D", "YYYY.MM.DD", "YYYY.0M", "YYYY.MM"];
+
+impl Version {
+    // Picks the first scheme that accepts the whole string:
+    // SemVer ("1.2.3-rc.1"), CalVer ("2020.06.07"), NumVer ("1.02.3.4"),
+    // then StrVer for anything else. Only "" is Missing.
+    pub fn classify(s: &str) -> Version {
+        if s.is_empty() {
+            return Version::Missing;
+        }
+        if let Ok(v) = s.parse() {
+            return Version::SemVer(v);
+        }
+        for format in CALVER_FORMATS.iter() {
+            match CalVer::parse(format, s) {
+                Ok(v) if (1970..2100).contains(&v.year) => return Version::CalVer(v),
+                _ => {}
+            }
+        }
+        if let Some(v) = NumVer::parse(s) {
+            return Version::NumVer(v);
+        }
+        Version::StrVer(s.to_string())
+    }
+
+    fn scheme_rank(&self) -> u8 {
+        match self {
+            Version::Missing => 0,
+            Version::SemVer(_) => 1,
+            Version::CalVer(_) => 2,
+            Version::NumVer(_) => 3,
+            Version::StrVer(_) => 4,
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Version::SemVer(a), Version::SemVer(b)) => a.cmp(b),
+            (Version::CalVer(a), Version::CalVer(b)) => a.cmp(b),
+            (Version::NumVer(a), Version::NumVer(b)) => a.cmp(b),
+            (Version::StrVer(a), Version::StrVer(b)) => a.cmp(b),
+            (a, b) => a.scheme_rank().cmp(&b.scheme_rank()),
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn cmp_pre_release(a: &Option<String>, b: &Option<String>) -> Ordering {
+    // a version without a pre-release has higher precedence than one with
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => {
+            let mut a_ids = a.split('.');
+            let mut b_ids = b.split('.');
+            loop {
+                match (a_ids.next(), b_ids.next()) {
+                    (None, None) => return Ordering::Equal,
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(x), Some(y)) => match cmp_identifier(x, y) {
+                        Ordering::Equal => continue,
+                        ord => return ord,
+                    },
+                }
+            }
+        }
+    }
+}
+
+fn cmp_identifier(a: &str, b: &str) -> Ordering {
+    // numeric identifiers compare numerically and always have lower
+    // precedence than alphanumeric ones, which compare in ASCII order
+    fn is_numeric(s: &str) -> bool {
+        !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit())
+    }
+    match (is_numeric(a), is_numeric(b)) {
+        (true, true) => cmp_numeric(a, b),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.cmp(b),
+    }
+}
+
+fn cmp_numeric(a: &str, b: &str) -> Ordering {
+    // compare by digit count first so arbitrarily large numbers work
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+// Equality and ordering are numeric and missing trailing numbers count as
+// zeros, so 1.02 == 1.2.0 although each displays as written.
+#[derive(Debug, Clone)]
+pub struct NumVer {
+    // 1.02.3
+    pub numbers: Vec<String>, // digits as written, leading zeros kept
+}
+
+pub type StrVer = String;
+
+impl NumVer {
+    pub fn parse(s: &str) -> Option<NumVer> {
+        let numbers: Vec<String> = s.split('.').map(String::from).collect();
+        if numbers.iter().all(|n| !n.is_empty() && n.bytes().all(|c| c.is_ascii_digit())) {
+            Some(NumVer { numbers })
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for NumVer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.numbers.join("."))
+    }
+}
+
+impl Ord for NumVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.numbers.len().max(other.numbers.len());
+        (0..len)
+            .map(|i| {
+                let a = self.numbers.get(i).map_or("0", |n| n.as_str());
+                let b = other.numbers.get(i).map_or("0", |n| n.as_str());
+                cmp_numeric(a, b)
+            })
+            .find(|ord| *ord != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for NumVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for NumVer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for NumVer {}
+
+#[derive(Debug, Clone)]
+pub struct CalVer {
+    // https://calver.org/
+    pub format: String, // e.g. "YYYY.0M.0D", see calver::CalVer::parse
+    pub year: u16,
+    pub month: Option<u8>,
+    pub week: Option<u8>,
+    pub day: Option<u8>,
+    pub major: Option<u32>,
+    pub minor: Option<u32>,
+    pub micro: Option<u32>,
+    pub build: Option<String>,
+    pub pre_release: Option<String>,
+}
+
+#[derive(Parser)]
+#[grammar = "semver.pest"]
+pub struct SemVerParser;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemVerError {
+    pub span: (usize, usize), // byte offsets into the input
+    pub kind: SemVerErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SemVerErrorKind {
+    Invalid,     // not MAJOR.MINOR.PATCH[-PRE][+BUILD]
+    LeadingZero, // "01.2.3", which npm and semver.org both reject
+    Overflow,    // number above MAX_NUMBER
+}
+
+impl fmt::Display for SemVerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let column = self.span.0 + 1;
+        match self.kind {
+            SemVerErrorKind::Invalid => write!(f, "invalid semver at column {}", column),
+            SemVerErrorKind::LeadingZero => write!(f, "leading zero at column {}", column),
+            SemVerErrorKind::Overflow => write!(f, "version number too large at column {}", column),
+        }
+    }
+}
+
+impl Error for SemVerError {}
+
+impl FromStr for SemVer {
+    type Err = SemVerError;
+
+    // Strict https://semver.org/, the lenient npm reading that also takes
+    // partial versions is npm_semver::parse_partial.
+    fn from_str(input: &str) -> Result<SemVer, SemVerError> {
+        let error = |span, kind| SemVerError { span, kind };
+        let pair = match SemVerParser::parse(Rule::valid_semver, input) {
+            Ok(mut pairs) => pairs.next().unwrap(),
+            Err(e) => {
+                let span = match e.location {
+                    pest::error::InputLocation::Pos(p) => (p, p),
+                    pest::error::InputLocation::Span(s) => s,
+                };
+                return Err(error(span, SemVerErrorKind::Invalid));
+            }
+        };
+        if pair.as_str() != input {
+            // dangling chars
+            return Err(error((pair.as_str().len(), input.len()), SemVerErrorKind::Invalid));
+        }
+        let mut v = SemVer::new(0, 0, 0);
+        for p in pair.into_inner() {
+            match p.as_rule() {
+                Rule::version_core => {
+                    for field in p.into_inner() {
+                        let span = (field.as_span().start(), field.as_span().end());
+                        let s = field.as_str();
+                        if s.len() > 1 && s.starts_with('0') {
+                            return Err(error(span, SemVerErrorKind::LeadingZero));
+                        }
+                        let n = parse_number(s).ok_or_else(|| error(span, SemVerErrorKind::Overflow))?;
+                        match field.as_rule() {
+                            Rule::major => v.major = SemVerField::Number(n),
+                            Rule::minor => v.minor = SemVerField::Number(n),
+                            Rule::patch => v.patch = SemVerField::Number(n),
+                            _ => unreachable!(),
+                        }
+                    }
+                }
+                Rule::pre_release => v.pre_release = Some(p.as_str().to_string()),
+                Rule::build => v.build = Some(p.as_str().to_string()),
+                _ => unreachable!(),
+            }
+        }
+        Ok(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(major: u64, minor: u64, patch: u64, pre_release: Option<&str>) -> SemVer {
+        SemVer {
+            major: SemVerField::Number(major),
+            minor: SemVerField::Number(minor),
+            patch: SemVerField::Number(patch),
+            pre_release: pre_release.map(String::from),
+            build: None,
+        }
+    }
+
+    #[test]
+    fn test_semver_precedence() {
+        // https://semver.org/#spec-item-11
+        let ordered = vec![
+            sv(1, 0, 0, Some("alpha")),
+            sv(1, 0, 0, Some("alpha.1")),
+            sv(1, 0, 0, Some("alpha.2")),
+            sv(1, 0, 0, Some("alpha.10")),
+            sv(1, 0, 0, Some("alpha.beta")),
+            sv(1, 0, 0, Some("beta")),
+            sv(1, 0, 0, Some("beta.2")),
+            sv(1, 0, 0, Some("beta.11")),
+            sv(1, 0, 0, Some("rc.1")),
+            sv(1, 0, 0, None),
+            sv(1, 0, 1, None),
+            sv(1, 1, 0, None),
+            sv(1, 10, 0, None),
+            sv(2, 0, 0, None),
+        ];
+        for (i, a) in ordered.iter().enumerate() {
+            for (j, b) in ordered.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "{:?} vs {:?}", a, b);
+            }
+        }
+        let mut shuffled: Vec<_> = ordered.iter().rev().collect();
+        shuffled.sort();
+        assert!(shuffled.into_iter().eq(ordered.iter()));
+    }
+
+    #[test]
+    fn test_semver_build_ignored() {
+        let mut a = sv(1, 0, 0, Some("rc.1"));
+        let mut b = sv(1, 0, 0, Some("rc.1"));
+        a.build = Some("001".to_string());
+        b.build = Some("exp.sha.5114f85".to_string());
+        assert_eq!(a, b);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_semver_field_policy() {
+        use SemVerField::*;
+        assert!(Missing < Number(0));
+        assert!(Number(0) < Number(MAX_NUMBER));
+        assert!(Number(MAX_NUMBER) < Wildcard);
+        let partial = SemVer { major: Number(1), minor: Missing, patch: Missing, pre_release: None, build: None };
+        let wildcard = SemVer { major: Number(1), minor: Wildcard, patch: Missing, pre_release: None, build: None };
+        assert!(partial < sv(1, 0, 0, Some("alpha")));
+        assert!(wildcard > sv(1, MAX_NUMBER, MAX_NUMBER, None));
+        assert!(wildcard < sv(2, 0, 0, Some("alpha")));
+    }
+
+    #[test]
+    fn test_version_ord() {
+        assert!(Version::Missing < Version::SemVer(sv(0, 0, 0, Some("0"))));
+        assert_eq!(Version::Missing, Version::Missing);
+        let mut versions = [
+            Version::SemVer(sv(1, 2, 3, None)),
+            Version::Missing,
+            Version::SemVer(sv(1, 2, 3, Some("beta"))),
+        ];
+        versions.sort();
+        assert_eq!(versions.iter().max(), Some(&Version::SemVer(sv(1, 2, 3, None))));
+        assert_eq!(versions[0], Version::Missing);
+
+        let calver = Version::CalVer(CalVer::parse("YYYY.0M.0D", "2020.06.07").unwrap());
+        assert!(calver > Version::SemVer(sv(MAX_NUMBER, 0, 0, None)));
+        assert!(calver < Version::CalVer(CalVer::parse("YY.0M", "20.07").unwrap()));
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(Version::classify("1.2.3-alpha+001"), Version::SemVer(SemVer {
+            pre_release: Some("alpha".to_string()),
+            build: Some("001".to_string()),
+            ..SemVer::new(1, 2, 3)
+        }));
+        assert!(matches!(Version::classify("2020.06.07"), Version::CalVer(_)));
+        assert!(matches!(Version::classify("2020.6"), Version::CalVer(_)));
+        assert!(matches!(Version::classify("2020.6.7"), Version::SemVer(_)));
+        assert!(matches!(Version::classify("1.02.3.4"), Version::NumVer(_)));
+        assert!(matches!(Version::classify("01.2.3"), Version::NumVer(_)));
+        assert!(matches!(Version::classify("1.2.9007199254740992"), Version::NumVer(_)));
+        assert!(matches!(Version::classify("1.0.30001157"), Version::SemVer(_)));
+        assert!(matches!(Version::classify("20"), Version::NumVer(_)));
+        assert_eq!(Version::classify("1.0-SNAPSHOT"), Version::StrVer("1.0-SNAPSHOT".to_string()));
+        assert_eq!(Version::classify(""), Version::Missing);
+        for s in ["1.2.3-rc.1", "2020.06.07", "1.02.3.4", "latest"].iter() {
+            assert_eq!(Version::classify(s).to_string(), *s);
+        }
+    }
+
+    #[test]
+    fn test_numver_ord() {
+        let n = |s| NumVer::parse(s).unwrap();
+        assert!(n("1.2.3.4") < n("1.2.3.10"));
+        assert!(n("1.9") < n("1.10"));
+        assert!(n("1.2") < n("1.2.0.1"));
+        assert_eq!(n("1.02"), n("1.2.0"));
+        assert_eq!(n("1.02").to_string(), "1.02");
+        assert_eq!(NumVer::parse("1..2"), None);
+        assert_eq!(NumVer::parse("1.2a"), None);
+    }
+
+    #[test]
+    fn test_semver_from_str() {
+        let v: SemVer = "1.2.3-alpha.1+001".parse().unwrap();
+        assert_eq!(v, sv(1, 2, 3, Some("alpha.1")));
+        assert_eq!(v.build.as_deref(), Some("001"));
+        assert_eq!("0.0.0".parse(), Ok(SemVer::new(0, 0, 0)));
+
+        let kind = |s: &str| s.parse::<SemVer>().map_err(|e| (e.span, e.kind));
+        use SemVerErrorKind::*;
+        assert_eq!(kind("1.2"), Err(((3, 3), Invalid)));
+        assert_eq!(kind("1.2.x"), Err(((4, 4), Invalid)));
+        assert_eq!(kind("v1.2.3"), Err(((0, 0), Invalid)));
+        assert_eq!(kind("1.2.3-01"), Err(((7, 8), Invalid)));
+        assert_eq!(kind("1.02.3"), Err(((2, 4), LeadingZero)));
+        assert_eq!(kind("1.2.9007199254740992"), Err(((4, 20), Overflow)));
+        let e = "1.02.3".parse::<SemVer>().unwrap_err();
+        assert_eq!(e.to_string(), "leading zero at column 3");
+    }
+
+    #[test]
+    fn test_semver_successful_parse() {
+        let inputs = vec!["1.2.3", "1.2.3-alpha", "1.2.3-alpha+001", "1.2.3+exp.46"];
+        for i in inputs {
+            match SemVerParser::parse(Rule::valid_semver, i) {
+                Ok(p) => assert_eq!(p.as_str(), i), // ensure complete parsing
+                Err(e) => panic!("error: {}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_semver_unsuccessful_parse() {
+        let inputs = vec!["this is not a semver", "2.0", "1.2.3-+r46-a"];
+        for i in inputs {
+            match SemVerParser::parse(Rule::valid_semver, i) {
+                Ok(p) => {
+                    if p.as_str() != i {
+                        // dangling chars means incomplete parse, this is fine
+                        continue
+                    } else {
+                        panic!("should not have been successful: {} => {:#?}", i, p)
+                    }
+                },
+                Err(_) => (),
+            }
+        }
+    }
+}
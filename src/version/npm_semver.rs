@@ -0,0 +1,703 @@
+use std::error::Error;
+use std::fmt;
+
+use lazy_static::lazy_static;
+use pest::Parser;
+use pest::error::{Error as PestError, ErrorVariant, InputLocation};
+use pest::iterators::{Pair, Pairs};
+use pest::prec_climber::{Assoc, Operator, PrecClimber};
+
+use super::{parse_number, Version, VersionQuery as VQ, SemVer, SemVerField};
+
+#[derive(Parser)]
+#[grammar = "version/npm_semver.pest"]
+struct NpmSemVerParser;
+
+lazy_static! {
+    static ref VQ_PREC_CLIMBER: PrecClimber<Rule> = {
+        use Assoc::*;
+        PrecClimber::new(vec![
+            Operator::new(Rule::logical_or, Left),
+            Operator::new(Rule::logical_and, Left),
+            Operator::new(Rule::hyphen, Left),
+        ])
+    };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeError {
+    pub span: (usize, usize), // byte offsets into the input
+    pub kind: RangeErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RangeErrorKind {
+    Unexpected { expected: Vec<&'static str> },
+    Overflow, // number above MAX_NUMBER
+    LooseOnly(&'static str), // e.g. a leading zero, fine with parse_range_loose
+}
+
+impl RangeError {
+    // 1-based, as shown to developers
+    pub fn column(&self) -> usize {
+        self.span.0 + 1
+    }
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            RangeErrorKind::Unexpected { expected } if expected.is_empty() => {
+                write!(f, "unexpected input at column {}", self.column())
+            }
+            RangeErrorKind::Unexpected { expected } => {
+                write!(f, "expected {} at column {}", expected.join(" or "), self.column())
+            }
+            RangeErrorKind::Overflow => {
+                write!(f, "version number too large at column {}", self.column())
+            }
+            RangeErrorKind::LooseOnly(what) => {
+                write!(f, "{} at column {} is only allowed in loose mode", what, self.column())
+            }
+        }
+    }
+}
+
+impl Error for RangeError {}
+
+impl From<PestError<Rule>> for RangeError {
+    fn from(e: PestError<Rule>) -> Self {
+        let span = match e.location {
+            InputLocation::Pos(p) => (p, p),
+            InputLocation::Span(s) => s,
+        };
+        let mut expected = vec![];
+        if let ErrorVariant::ParsingError { positives, .. } = e.variant {
+            for rule in positives {
+                let token = expected_token(rule);
+                if !expected.contains(&token) {
+                    expected.push(token);
+                }
+            }
+        }
+        RangeError { span, kind: RangeErrorKind::Unexpected { expected } }
+    }
+}
+
+fn expected_token(rule: Rule) -> &'static str {
+    match rule {
+        Rule::logical_or => "\"||\"",
+        Rule::logical_and => "\" \"",
+        Rule::hyphen => "\" - \"",
+        Rule::comparator | Rule::gte | Rule::lte | Rule::gt | Rule::lt | Rule::eq => "comparator",
+        Rule::approx => "\"~\"",
+        Rule::compat => "\"^\"",
+        Rule::qualifier | Rule::pre | Rule::build | Rule::parts | Rule::part => "identifier",
+        Rule::EOI => "end of input",
+        _ => "version",
+    }
+}
+
+// https://docs.npmjs.com/misc/semver#ranges
+pub fn parse_range(input: &str) -> Result<VQ, RangeError> {
+    range(input, false)
+}
+
+// node-semver's loose mode, which also takes leading zeros and pre-releases
+// without "-": "01.2.3beta"
+pub fn parse_range_loose(input: &str) -> Result<VQ, RangeError> {
+    range(input, true)
+}
+
+fn range(input: &str, loose: bool) -> Result<VQ, RangeError> {
+    let mut pairs = NpmSemVerParser::parse(Rule::version_range, input)?;
+    if !loose {
+        check_strict(pairs.clone())?;
+    }
+    let range_set = pairs.next().unwrap().into_inner().next().unwrap();
+    eval_vq(range_set.into_inner())
+}
+
+// The grammar accepts loose input, this rejects what only loose mode allows
+fn check_strict(pairs: Pairs<Rule>) -> Result<(), RangeError> {
+    let loose_only = |pair: &Pair<Rule>, what| {
+        let span = pair.as_span();
+        Err(RangeError { span: (span.start(), span.end()), kind: RangeErrorKind::LooseOnly(what) })
+    };
+    let leading_zero = |s: &str| s.len() > 1 && s.starts_with('0') && s.bytes().all(|c| c.is_ascii_digit());
+    for pair in pairs.flatten() {
+        match pair.as_rule() {
+            Rule::nr if leading_zero(pair.as_str()) => return loose_only(&pair, "leading zero"),
+            Rule::qualifier => {
+                let has_pre = pair.clone().into_inner().any(|p| p.as_rule() == Rule::pre);
+                if has_pre && !pair.as_str().starts_with('-') {
+                    return loose_only(&pair, "pre-release without \"-\"");
+                }
+            }
+            Rule::pre => {
+                for part in pair.into_inner().flatten().filter(|p| p.as_rule() == Rule::part) {
+                    if leading_zero(part.as_str()) {
+                        return loose_only(&part, "leading zero");
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// Lenient counterpart of SemVer::from_str: partial versions and wildcards
+// are kept as written, "1.x" => 1.x rather than an error.
+pub fn parse_partial(input: &str) -> Result<SemVer, RangeError> {
+    let mut pairs = NpmSemVerParser::parse(Rule::partial_version, input)?;
+    check_strict(pairs.clone())?;
+    let partial = pairs.next().unwrap().into_inner().next().unwrap();
+    match coerce_partial(partial)? {
+        Version::SemVer(v) => Ok(v),
+        _ => unreachable!(),
+    }
+}
+
+fn coerce_partial(pair: Pair<Rule>) -> Result<Version, RangeError> {
+    // https://docs.npmjs.com/misc/semver#coercion
+    assert_eq!(pair.as_rule(), Rule::partial);
+
+    fn to_field(maybe_xr: Option<Pair<Rule>>) -> Result<SemVerField, RangeError> {
+        match maybe_xr {
+            Some(xr) => {
+                assert_eq!(xr.as_rule(), Rule::xr);
+                match xr.into_inner().next() {
+                    Some(nr) => {
+                        assert_eq!(nr.as_rule(), Rule::nr);
+                        let span = nr.as_span();
+                        parse_number(nr.as_str())
+                            .map(SemVerField::Number)
+                            .ok_or(RangeError {
+                                span: (span.start(), span.end()),
+                                kind: RangeErrorKind::Overflow,
+                            })
+                    },
+                    None => Ok(SemVerField::Wildcard),
+                }
+            },
+            None => Ok(SemVerField::Missing),
+        }
+    }
+    let mut pairs = pair.into_inner();
+    let major = to_field(pairs.next())?;
+    let minor = to_field(pairs.next())?;
+    let patch = to_field(pairs.next())?;
+    let mut pre_release = None;
+    let mut build = None;
+    if let Some(qualifier_pair) = pairs.next() {
+        for p in qualifier_pair.into_inner() {
+            match p.as_rule() {
+                Rule::pre => { pre_release = Some(String::from(p.as_str())); }
+                Rule::build => { build = Some(String::from(p.as_str())); }
+                _ => unreachable!(),
+            }
+        }
+    }
+    Ok(Version::SemVer(SemVer { major, minor, patch, pre_release, build }))
+}
+
+fn eval_vq(expression: Pairs<Rule>) -> Result<VQ, RangeError> {
+    VQ_PREC_CLIMBER.climb(
+        expression,
+        |pair: Pair<Rule>| match pair.as_rule() {
+            Rule::range_set => eval_vq(pair.into_inner()),
+            Rule::range => {
+                // "" is the same as "*"
+                let inner = pair.into_inner();
+                match inner.peek() {
+                    Some(_) => eval_vq(inner),
+                    None => Ok(VQ::Version(Version::SemVer(SemVer {
+                        major: SemVerField::Wildcard,
+                        minor: SemVerField::Missing,
+                        patch: SemVerField::Missing,
+                        pre_release: None,
+                        build: None,
+                    }))),
+                }
+            },
+            Rule::hyphen_range => eval_vq(pair.into_inner()),
+            Rule::simple => eval_vq(pair.into_inner()),
+            Rule::partial => Ok(VQ::Version(coerce_partial(pair)?)),
+            Rule::primitive => {
+                let mut primitive_inner = pair.into_inner();
+                let cmp_husk = primitive_inner.next().unwrap();
+                assert_eq!(cmp_husk.as_rule(), Rule::comparator);
+                let mut cmp_item = cmp_husk.into_inner();
+                let cmp = cmp_item.next().unwrap().as_rule();
+                let partial = primitive_inner.next().unwrap();
+                let v = coerce_partial(partial)?;
+                Ok(match cmp {
+                    Rule::gte => VQ::Gte(v),
+                    Rule::lte => VQ::Lte(v),
+                    Rule::gt  => VQ::Gt(v),
+                    Rule::lt  => VQ::Lt(v),
+                    Rule::eq  => VQ::Eq(v),
+                    _ => unreachable!(),
+                })
+            },
+            Rule::approx => Ok(VQ::Approx(coerce_partial(pair.into_inner().next().unwrap())?)),
+            Rule::compat => Ok(VQ::Compat(coerce_partial(pair.into_inner().next().unwrap())?)),
+            a => unreachable!("got to {:?}", a),
+        },
+        |lhs: Result<VQ, RangeError>, op: Pair<Rule>, rhs: Result<VQ, RangeError>| {
+            let (lhs, rhs) = (Box::new(lhs?), Box::new(rhs?));
+            Ok(match op.as_rule() {
+                Rule::hyphen      => VQ::Range(lhs, rhs),
+                Rule::logical_and => VQ::And(lhs, rhs),
+                Rule::logical_or  => VQ::Or(lhs, rhs),
+                _ => unreachable!(),
+            })
+        },
+    )
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap as Map;
+
+    #[test]
+    fn test_parse_npm_semver() {
+        let inputs = vec![
+            "1.2.3", "1.2.3-alpha", "1.2.3-alpha+001", "1.2.3+exp.46",
+            "1.2.0 - 1.3.0",
+            ">=1.2.3", "<=1.2.3", "1.2.3 || 1.2.4",
+            ">=1.2.3 <2.0",
+        ];
+        for i in inputs {
+            match NpmSemVerParser::parse(Rule::range_set, i) {
+                Ok(p) => assert_eq!(p.as_str(), i), // ensure complete parsing
+                Err(e) => panic!("error: {}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let inputs = vec![
+            "1.2.3", "1.2.3-alpha", "1.2.3-alpha+001", "1.2.3+exp.46",
+            "1.2.0 - 1.3.0",
+            ">=1.2.3", "<=1.2.3", "1.2.3 || 1.2.4",
+            ">=1.2.3 <2.0",
+            "^5.1.6 ^6.1.2 || <=1.x", "~1.2 || 2.x.x - 3", "=1.2.3-rc.1",
+        ];
+        for i in inputs {
+            let vq = parse_range(i).unwrap();
+            assert_eq!(vq.to_string(), i);
+            assert_eq!(parse_range(&vq.to_string()).unwrap(), vq);
+        }
+    }
+
+    #[test]
+    fn test_coerce_partial() {
+        use SemVerField::*;
+        let mut expected_io = Map::new();
+        expected_io.insert("1", Version::SemVer(SemVer{
+            major: Number(1), minor: Missing, patch: Missing,
+            pre_release: None, build: None,
+        }));
+        expected_io.insert("1.2", Version::SemVer(SemVer{
+            major: Number(1), minor: Number(2), patch: Missing,
+            pre_release: None, build: None,
+        }));
+        expected_io.insert("1.2.3", Version::SemVer(SemVer{
+            major: Number(1), minor: Number(2), patch: Number(3),
+            pre_release: None, build: None,
+        }));
+        expected_io.insert("1.2.3.4", Version::SemVer(SemVer{
+            major: Number(1), minor: Number(2), patch: Number(3),
+            pre_release: None, build: None,
+        }));
+        expected_io.insert("1.2.3-alpha+r77", Version::SemVer(SemVer{
+            major: Number(1), minor: Number(2), patch: Number(3),
+            pre_release: Some("alpha".to_string()), build: Some("r77".to_string()),
+        }));
+        for (k, v) in expected_io.iter() {
+            let mut pair = match NpmSemVerParser::parse(Rule::partial, k) {
+                Ok(p) => p,
+                Err(e) => panic!("error: {}", e),
+            };
+            assert_eq!(*v, coerce_partial(pair.next().unwrap()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_eval_vq_gte() {
+        use SemVerField::*;
+        let input = ">=5.2";
+        let pair = match NpmSemVerParser::parse(Rule::primitive, input) {
+            Ok(p) => p,
+            Err(e) => panic!("error: {}", e),
+        };
+        let vq = eval_vq(pair).unwrap();
+        if let VQ::Gte(Version::SemVer(v)) = vq {
+            assert_eq!(v.major, Number(5));
+            assert_eq!(v.minor, Number(2));
+            assert_eq!(v.patch, Missing);
+        } else {
+            unreachable!();
+        }
+    }
+
+    #[test]
+    fn test_eval_vq_compat() {
+        use SemVerField::*;
+        let input = "^5.1.6";
+        let pair = match NpmSemVerParser::parse(Rule::compat, input) {
+            Ok(p) => p,
+            Err(e) => panic!("error: {}", e),
+        };
+        let vq = eval_vq(pair).unwrap();
+        if let VQ::Compat(Version::SemVer(v)) = vq {
+            assert_eq!(v.major, Number(5));
+            assert_eq!(v.minor, Number(1));
+            assert_eq!(v.patch, Number(6));
+        } else {
+            unreachable!();
+        }
+    }
+
+    #[test]
+    fn test_eval_vq_logical() {
+        use VQ::{And, Or};
+        use SemVerField::*;
+        let input = "^5.1.6 ^6.1.2 || <=1.*";
+        let pairs = match NpmSemVerParser::parse(Rule::range_set, input) {
+            Ok(p) => p,
+            Err(e) => panic!("error: {}", e),
+        };
+        let vq = eval_vq(pairs).unwrap();
+        let expected_vq = Or(
+            Box::new(And(
+                Box::new(VQ::Compat(Version::SemVer(SemVer {
+                    major: Number(5),
+                    minor: Number(1),
+                    patch: Number(6),
+                    pre_release: None,
+                    build: None,
+                }))),
+                Box::new(VQ::Compat(Version::SemVer(SemVer {
+                    major: Number(6),
+                    minor: Number(1),
+                    patch: Number(2),
+                    pre_release: None,
+                    build: None,
+                }))),
+            )),
+            Box::new(VQ::Lte(Version::SemVer(SemVer {
+                major: Number(1),
+                minor: Wildcard,
+                patch: Missing,
+                pre_release: None,
+                build: None,
+            }))),
+        );
+        assert_eq!(expected_vq, vq);
+    }
+
+    #[test]
+    fn test_eval_vq_range() {
+        use SemVerField::*;
+        let input = "1 - 2";
+        let pairs = match NpmSemVerParser::parse(Rule::hyphen_range, input) {
+            Ok(p) => p,
+            Err(e) => panic!("error: {}", e),
+        };
+        let vq = eval_vq(pairs).unwrap();
+        let expected_vq = VQ::Range(
+            Box::new(VQ::Version(Version::SemVer(SemVer {
+                major: Number(1),
+                minor: Missing,
+                patch: Missing,
+                pre_release: None,
+                build: None,
+            }))),
+            Box::new(VQ::Version(Version::SemVer(SemVer {
+                major: Number(2),
+                minor: Missing,
+                patch: Missing,
+                pre_release: None,
+                build: None,
+            }))),
+        );
+        assert_eq!(expected_vq, vq);
+    }
+
+    fn parse_version(input: &str) -> Version {
+        let mut pairs = NpmSemVerParser::parse(Rule::partial, input)
+            .unwrap_or_else(|e| panic!("error: {}", e));
+        assert_eq!(pairs.as_str(), input);
+        coerce_partial(pairs.next().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_range() {
+        use SemVerField::*;
+        let vq = parse_range("^1.2.3 || >=2.0.0-beta <3").unwrap();
+        assert!(matches!(vq, VQ::Or(_, _)));
+        assert_eq!(parse_range("").unwrap(), parse_range("*").unwrap());
+        assert_eq!(parse_range(" 1.2 ").unwrap(), VQ::Version(Version::SemVer(SemVer {
+            major: Number(1), minor: Number(2), patch: Missing,
+            pre_release: None, build: None,
+        })));
+    }
+
+    #[test]
+    fn test_parse_partial() {
+        for input in ["1.2.3", "1.2", "1.x", "1.2.3-beta+b7"].iter() {
+            assert_eq!(parse_partial(input).unwrap().to_string(), *input);
+        }
+        // lenient where strict semver is not
+        assert!(parse_partial("1.2").is_ok());
+        assert!("1.2".parse::<SemVer>().is_err());
+        assert_eq!(parse_partial("^1.2").unwrap_err().span, (0, 0));
+        assert_eq!(parse_partial("1.2.3 ").unwrap_err().span, (5, 5));
+    }
+
+    #[test]
+    fn test_parse_range_node_syntax() {
+        let cases = [
+            (">= 1.2.3", ">=1.2.3"),
+            ("<\t2.0.0", "<2.0.0"),
+            ("~ 1.0", "~1.0"),
+            ("~>3.2.1", "~3.2.1"),
+            ("^ 1.2.3", "^1.2.3"),
+            ("v1.2.3", "1.2.3"),
+            ("=v1.2.3", "=1.2.3"),
+            ("v1.2.3 - v2", "1.2.3 - 2"),
+            ("1.x - 2.x", "1.x - 2.x"),
+            ("10.0.0 - 11.20.3", "10.0.0 - 11.20.3"),
+            ("1.2.3  ||2", "1.2.3 || 2"),
+            ("||", "x || x"),
+        ];
+        for (input, expected) in cases.iter() {
+            assert_eq!(parse_range(input).unwrap().to_string(), *expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_range_loose() {
+        let e = parse_range("01.2.3").unwrap_err();
+        assert_eq!((e.span, e.kind.clone()), ((0, 2), RangeErrorKind::LooseOnly("leading zero")));
+        assert_eq!(e.to_string(), "leading zero at column 1 is only allowed in loose mode");
+        assert_eq!(parse_range_loose("01.2.3").unwrap(), parse_range("1.2.3").unwrap());
+
+        let e = parse_range(">=1.2.3beta").unwrap_err();
+        assert_eq!(e.kind, RangeErrorKind::LooseOnly("pre-release without \"-\""));
+        assert_eq!(e.column(), 8);
+        assert_eq!(parse_range_loose(">=1.2.3beta").unwrap().to_string(), ">=1.2.3-beta");
+
+        let e = parse_range("1.2.3-alpha.01").unwrap_err();
+        assert_eq!(e.span, (12, 14));
+        assert!(parse_range_loose("1.2.3-alpha.01").is_ok());
+        assert!(parse_partial("01.2").is_err());
+    }
+
+    #[test]
+    fn test_parse_range_errors() {
+        let e = parse_range("^1.2.3 foo").unwrap_err();
+        assert_eq!(e.column(), 8);
+        assert_eq!(e.to_string(), "expected end of input or version at column 8");
+        assert!(matches!(e.kind, RangeErrorKind::Unexpected { .. }));
+
+        let e = parse_range(">=1.2.3 || <9007199254740992").unwrap_err();
+        assert_eq!(e.span, (12, 28));
+        assert_eq!(e.kind, RangeErrorKind::Overflow);
+        assert_eq!(e.to_string(), "version number too large at column 13");
+
+        let e = parse_range("1.2.3 -").unwrap_err();
+        match e.kind {
+            RangeErrorKind::Unexpected { expected } => assert!(expected.contains(&"end of input")),
+            k => panic!("unexpected error kind {:?}", k),
+        }
+    }
+
+    #[test]
+    fn test_matches_range_include() {
+        // https://github.com/npm/node-semver/blob/master/test/fixtures/range-include.js
+        let cases = [
+            ("1.0.0 - 2.0.0", "1.2.3"),
+            ("^1.2.3+build", "1.2.3"),
+            ("^1.2.3+build", "1.3.0"),
+            ("1.2.3-pre+asdf - 2.4.3-pre+asdf", "1.2.3"),
+            ("1.2.3-pre+asdf - 2.4.3-pre+asdf", "1.2.3-pre.2"),
+            ("1.2.3-pre+asdf - 2.4.3-pre+asdf", "2.4.3-alpha"),
+            ("1.2.3+asdf - 2.4.3+asdf", "1.2.3"),
+            ("1.0.0", "1.0.0"),
+            (">=*", "0.2.4"),
+            ("*", "1.2.3"),
+            (">=1.0.0", "1.0.0"),
+            (">=1.0.0", "1.0.1"),
+            (">=1.0.0", "1.1.0"),
+            (">1.0.0", "1.0.1"),
+            (">1.0.0", "1.1.0"),
+            ("<=2.0.0", "2.0.0"),
+            ("<=2.0.0", "1.9999.9999"),
+            ("<=2.0.0", "0.2.9"),
+            ("<2.0.0", "1.9999.9999"),
+            ("<2.0.0", "0.2.9"),
+            (">=0.1.97", "0.1.97"),
+            (">=0.2.3 || <0.0.1", "0.0.0"),
+            (">=0.2.3 || <0.0.1", "0.2.3"),
+            (">=0.2.3 || <0.0.1", "0.2.4"),
+            ("2.x.x", "2.1.3"),
+            ("1.2.x", "1.2.3"),
+            ("1.2.x || 2.x", "2.1.3"),
+            ("1.2.x || 2.x", "1.2.3"),
+            ("x", "1.2.3"),
+            ("2.*.*", "2.1.3"),
+            ("1.2.*", "1.2.3"),
+            ("1.2.* || 2.*", "2.1.3"),
+            ("2", "2.1.2"),
+            ("2.3", "2.3.1"),
+            ("~0.0.1", "0.0.1"),
+            ("~0.0.1", "0.0.2"),
+            ("~x", "0.0.9"),
+            ("~2", "2.0.9"),
+            ("~2.4", "2.4.0"),
+            ("~2.4", "2.4.5"),
+            ("~1", "1.2.3"),
+            ("~1.0", "1.0.2"),
+            (">=1", "1.0.0"),
+            ("<1.2", "1.1.1"),
+            ("~1.2.1 >=1.2.3", "1.2.3"),
+            ("~1.2.1 =1.2.3", "1.2.3"),
+            ("~1.2.1 1.2.3", "1.2.3"),
+            ("~1.2.1 >=1.2.3 1.2.3", "1.2.3"),
+            (">=1.2.1 1.2.3", "1.2.3"),
+            ("1.2.3 >=1.2.1", "1.2.3"),
+            (">=1.2.3 >=1.2.1", "1.2.3"),
+            (">=1.2.1 >=1.2.3", "1.2.3"),
+            (">=1.2", "1.2.8"),
+            ("^1.2.3", "1.8.1"),
+            ("^0.1.2", "0.1.2"),
+            ("^0.1", "0.1.2"),
+            ("^0.0.1", "0.0.1"),
+            ("^1.2", "1.4.2"),
+            ("^1.2 ^1", "1.4.2"),
+            ("^1.2.3-alpha", "1.2.3-pre"),
+            ("^1.2.0-alpha", "1.2.0-pre"),
+            ("^0.0.1-alpha", "0.0.1-beta"),
+            ("^0.0.1-alpha", "0.0.1"),
+            ("^0.1.1-alpha", "0.1.1-beta"),
+            ("^x", "1.2.3"),
+            ("x - 1.0.0", "0.9.7"),
+            ("x - 1.x", "0.9.7"),
+            ("1.0.0 - x", "1.9.7"),
+            ("1.x - x", "1.9.7"),
+            ("<=7.x", "7.9.9"),
+            (">= 1.0.0", "1.0.0"),
+            ("<=  2.0.0", "0.2.9"),
+            ("<\t2.0.0", "0.2.9"),
+            ("||", "1.3.4"),
+            ("~ 1.0", "1.0.2"),
+            ("~>3.2.1", "3.2.2"),
+            ("~> 1", "1.2.3"),
+            ("~v0.5.4-pre", "0.5.5"),
+            ("~v0.5.4-pre", "0.5.4"),
+            ("=0.7.x", "0.7.2"),
+            ("0.1.20 || 1.2.4", "1.2.4"),
+            ("^ 1.2.3", "1.8.1"),
+            ("10.0.0 - 11.2.3", "10.5.0"),
+            ("^10.2.2", "10.9.1"),
+            ("^1.0.30001157", "1.0.30001200"),
+        ];
+        for (range, version) in cases.iter() {
+            assert!(parse_range(range).unwrap().matches(&parse_version(version)),
+                    "{} should include {}", range, version);
+        }
+    }
+
+    #[test]
+    fn test_matches_range_exclude() {
+        // https://github.com/npm/node-semver/blob/master/test/fixtures/range-exclude.js
+        let cases = [
+            ("1.0.0 - 2.0.0", "2.2.3"),
+            ("^1.0.30001157", "1.0.30001156"),
+            ("1.2.3+asdf - 2.4.3+asdf", "1.2.3-pre.2"),
+            ("1.2.3+asdf - 2.4.3+asdf", "2.4.3-alpha"),
+            ("^1.2.3+build", "2.0.0"),
+            ("^1.2.3+build", "1.2.0"),
+            ("^1.2.3", "1.2.3-pre"),
+            ("^1.2", "1.2.0-pre"),
+            (">1.2", "1.3.0-beta"),
+            ("<=1.2.3", "1.2.3-beta"),
+            ("^1.2.3", "1.2.3-beta"),
+            ("=0.7.x", "0.7.0-asdf"),
+            (">=0.7.x", "0.7.0-asdf"),
+            ("<=0.7.x", "0.7.0-asdf"),
+            ("1.0.0", "1.0.1"),
+            (">=1.0.0", "0.0.0"),
+            (">=1.0.0", "0.0.1"),
+            (">=1.0.0", "0.1.0"),
+            (">1.0.0", "0.0.1"),
+            (">1.0.0", "0.1.0"),
+            ("<=2.0.0", "3.0.0"),
+            ("<=2.0.0", "2.9999.9999"),
+            ("<=2.0.0", "2.2.9"),
+            ("<2.0.0", "2.9999.9999"),
+            ("<2.0.0", "2.2.9"),
+            (">=0.1.97", "0.1.93"),
+            (">=0.2.3 || <0.0.1", "0.0.3"),
+            (">=0.2.3 || <0.0.1", "0.2.2"),
+            ("2.x.x", "1.1.3"),
+            ("2.x.x", "3.1.3"),
+            ("1.2.x", "1.3.3"),
+            ("1.2.x || 2.x", "3.1.3"),
+            ("1.2.x || 2.x", "1.1.3"),
+            ("2.*.*", "1.1.3"),
+            ("2.*.*", "3.1.3"),
+            ("1.2.*", "1.3.3"),
+            ("2", "1.1.2"),
+            ("2.3", "2.4.1"),
+            ("~0.0.1", "0.1.0-alpha"),
+            ("~0.0.1", "0.1.0"),
+            ("~2.4", "2.5.0"),
+            ("~2.4", "2.3.9"),
+            ("~1", "0.2.3"),
+            ("~1.0", "1.1.0"),
+            ("<1", "1.0.0"),
+            (">=1.2", "1.1.1"),
+            ("=0.7.x", "0.8.2"),
+            (">=0.7.x", "0.6.2"),
+            ("<0.7.x", "0.7.2"),
+            ("<1.2.3", "1.2.3-beta"),
+            ("=1.2.3", "1.2.3-beta"),
+            (">1.2", "1.2.8"),
+            ("^0.0.1", "0.0.2"),
+            ("^1.2.3", "2.0.0-alpha"),
+            ("^1.2.3", "1.2.2"),
+            ("^1.2", "1.1.9"),
+            ("*", "1.2.3-foo"),
+            ("^1.0.0", "2.0.0-rc1"),
+            ("^1.0.0", "1.0.0-rc1"),
+            ("1 - 2", "2.0.0-pre"),
+            ("1 - 2", "1.0.0-pre"),
+            ("1.0 - 2", "1.0.0-pre"),
+            ("1.1.x", "1.0.0-a"),
+            ("1.1.x", "1.1.0-a"),
+            ("1.1.x", "1.2.0-a"),
+            ("1.x", "1.0.0-a"),
+            ("1.x", "1.1.0-a"),
+            ("1.x", "1.2.0-a"),
+            (">=1.0.0 <1.1.0", "1.1.0"),
+            (">=1.0.0 <1.1.0", "1.1.0-pre"),
+            (">=1.0.0 <1.1.0-pre", "1.1.0-pre"),
+            ("~>3.2.1", "3.3.2"),
+            ("~> 1", "2.2.3"),
+            ("^10.2.2", "11.0.0"),
+            ("1.20.x", "1.2.0"),
+        ];
+        for (range, version) in cases.iter() {
+            assert!(!parse_range(range).unwrap().matches(&parse_version(version)),
+                    "{} should exclude {}", range, version);
+        }
+    }
+}
@@ -0,0 +1,481 @@
+// node-semver style evaluation of a VersionQuery
+// https://github.com/npm/node-semver#advanced-range-syntax
+//
+// Every query desugars into a disjunction of comparator sets, a version
+// satisfies the query if it satisfies every comparator of any one set:
+//   "^1.2.3 || 2.x" => [[>=1.2.3, <2.0.0-0], [>=2.0.0, <3.0.0-0]]
+// An empty set matches everything, an empty list of sets matches nothing.
+use super::{next_number, SemVer, SemVerField, Version, VersionQuery};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Lt,  // <
+    Lte, // <=
+    Gt,  // >
+    Gte, // >=
+    Eq,  // =
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparator {
+    pub op: Op,
+    pub version: SemVer, // always complete, see SemVer::is_complete
+}
+
+impl Comparator {
+    pub fn new(op: Op, version: SemVer) -> Self {
+        Comparator { op, version }
+    }
+
+    pub fn test(&self, v: &SemVer) -> bool {
+        match self.op {
+            Op::Lt => *v < self.version,
+            Op::Lte => *v <= self.version,
+            Op::Gt => *v > self.version,
+            Op::Gte => *v >= self.version,
+            Op::Eq => *v == self.version,
+        }
+    }
+}
+
+impl VersionQuery {
+    pub fn matches(&self, version: &Version) -> bool {
+        self.matches_with(&self.comparator_sets(), version)
+    }
+
+    fn matches_with(&self, sets: &[Vec<Comparator>], version: &Version) -> bool {
+        match version {
+            Version::SemVer(_) => matches_sets(sets, version),
+            Version::Missing => false,
+            _ => self.matches_ordered(version),
+        }
+    }
+
+    // Versions of other schemes are compared against bounds of the same
+    // scheme only, ^ and ~ have no meaning outside of semver.
+    fn matches_ordered(&self, v: &Version) -> bool {
+        use VersionQuery::*;
+        let same_scheme = |bound: &super::Version| bound.scheme_rank() == v.scheme_rank();
+        match self {
+            And(lhs, rhs) => lhs.matches_ordered(v) && rhs.matches_ordered(v),
+            Or(lhs, rhs) => lhs.matches_ordered(v) || rhs.matches_ordered(v),
+            Not(q) => !q.matches_ordered(v),
+            Range(from, to) => match (leaf_version(from), leaf_version(to)) {
+                (Some(f), Some(t)) => same_scheme(f) && same_scheme(t) && f <= v && v <= t,
+                _ => false,
+            },
+            Lt(b) => same_scheme(b) && v < b,
+            Lte(b) => same_scheme(b) && v <= b,
+            Gt(b) => same_scheme(b) && v > b,
+            Gte(b) => same_scheme(b) && v >= b,
+            Eq(b) | Version(b) => v == b,
+            Approx(_) | Compat(_) => false,
+        }
+    }
+
+    // the highest version a manifest would resolve to, e.g. from the
+    // versions listed in a registry index
+    pub fn max_satisfying<'a, I>(&self, versions: I) -> Option<&'a Version>
+    where
+        I: IntoIterator<Item = &'a Version>,
+    {
+        let sets = self.comparator_sets();
+        versions.into_iter().filter(|v| self.matches_with(&sets, v)).max()
+    }
+
+    pub fn min_satisfying<'a, I>(&self, versions: I) -> Option<&'a Version>
+    where
+        I: IntoIterator<Item = &'a Version>,
+    {
+        let sets = self.comparator_sets();
+        versions.into_iter().filter(|v| self.matches_with(&sets, v)).min()
+    }
+
+    // Bounds of another scheme than semver never match a semver version
+    pub fn comparator_sets(&self) -> Vec<Vec<Comparator>> {
+        use VersionQuery::*;
+        match self {
+            Lt(v) | Lte(v) | Gt(v) | Gte(v) | Eq(v) | Version(v) | Approx(v) | Compat(v)
+                if !is_semver_scheme(v) => none(),
+            Range(from, to) if ![from, to].iter()
+                .all(|q| leaf_version(q).is_none_or(is_semver_scheme)) => none(),
+            And(lhs, rhs) => intersect(lhs.comparator_sets(), rhs.comparator_sets()),
+            Or(lhs, rhs) => {
+                let mut sets = lhs.comparator_sets();
+                sets.extend(rhs.comparator_sets());
+                sets
+            }
+            Not(q) => negate(q.comparator_sets()),
+            Range(from, to) => hyphen(leaf_version(from), leaf_version(to)),
+            Lt(v) => primitive(Op::Lt, v),
+            Lte(v) => primitive(Op::Lte, v),
+            Gt(v) => primitive(Op::Gt, v),
+            Gte(v) => primitive(Op::Gte, v),
+            Eq(v) | Version(v) => primitive(Op::Eq, v),
+            Approx(v) => tilde(v),
+            Compat(v) => caret(v),
+        }
+    }
+}
+
+fn is_semver_scheme(v: &Version) -> bool {
+    matches!(v, Version::SemVer(_) | Version::Missing)
+}
+
+fn matches_sets(sets: &[Vec<Comparator>], version: &Version) -> bool {
+    match version {
+        Version::SemVer(v) if v.is_complete() => sets.iter().any(|set| test_set(set, v)),
+        _ => false,
+    }
+}
+
+fn test_set(set: &[Comparator], v: &SemVer) -> bool {
+    if !set.iter().all(|c| c.test(v)) {
+        return false;
+    }
+    if v.pre_release.is_some() {
+        // pre-releases are only let in by a comparator that opts in to
+        // pre-releases of the same [major, minor, patch] tuple
+        return set.iter().any(|c| {
+            let cv = &c.version;
+            cv.pre_release.is_some()
+                && (cv.major, cv.minor, cv.patch) == (v.major, v.minor, v.patch)
+        });
+    }
+    true
+}
+
+fn intersect(lhs: Vec<Vec<Comparator>>, rhs: Vec<Vec<Comparator>>) -> Vec<Vec<Comparator>> {
+    let mut sets = Vec::with_capacity(lhs.len() * rhs.len());
+    for l in lhs.iter() {
+        for r in rhs.iter() {
+            sets.push(l.iter().chain(r.iter()).cloned().collect());
+        }
+    }
+    sets
+}
+
+fn negate(sets: Vec<Vec<Comparator>>) -> Vec<Vec<Comparator>> {
+    // De Morgan: !(a && b || c) == (!a || !b) && !c
+    sets.into_iter().fold(vec![vec![]], |acc, set| {
+        let negated = set.into_iter().flat_map(|c| match c.op {
+            Op::Lt => vec![vec![Comparator::new(Op::Gte, c.version)]],
+            Op::Lte => vec![vec![Comparator::new(Op::Gt, c.version)]],
+            Op::Gt => vec![vec![Comparator::new(Op::Lte, c.version)]],
+            Op::Gte => vec![vec![Comparator::new(Op::Lt, c.version)]],
+            Op::Eq => vec![
+                vec![Comparator::new(Op::Lt, c.version.clone())],
+                vec![Comparator::new(Op::Gt, c.version)],
+            ],
+        }).collect();
+        intersect(acc, negated)
+    })
+}
+
+// Range children are plain versions, anything else is treated as "*"
+fn leaf_version(q: &VersionQuery) -> Option<&Version> {
+    use VersionQuery::*;
+    match q {
+        Lt(v) | Lte(v) | Gt(v) | Gte(v) | Eq(v) | Approx(v) | Compat(v) | Version(v) => Some(v),
+        _ => None,
+    }
+}
+
+// The numeric prefix of a partial version; every field after the first
+// x, * or missing one is treated as missing too, so "1.x.3" is "1.x".
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre_release: Option<String>,
+}
+
+impl Partial {
+    fn from(v: Option<&Version>) -> Self {
+        fn number(f: SemVerField) -> Option<u64> {
+            match f {
+                SemVerField::Number(n) => Some(n),
+                _ => None,
+            }
+        }
+        match v {
+            Some(Version::SemVer(sv)) => {
+                let major = number(sv.major);
+                let minor = major.and(number(sv.minor));
+                let patch = minor.and(number(sv.patch));
+                let pre_release = patch.and(sv.pre_release.clone());
+                Partial { major, minor, patch, pre_release }
+            }
+            _ => Partial { major: None, minor: None, patch: None, pre_release: None },
+        }
+    }
+
+    fn complete(&self) -> Option<SemVer> {
+        let mut v = SemVer::new(self.major?, self.minor?, self.patch?);
+        v.pre_release = self.pre_release.clone();
+        Some(v)
+    }
+}
+
+// the lowest possible version of a release, e.g. 2.0.0-0
+fn floor(major: u64, minor: u64, patch: u64) -> SemVer {
+    let mut v = SemVer::new(major, minor, patch);
+    v.pre_release = Some("0".to_string());
+    v
+}
+
+// exclusive upper bounds, None when the bump overflows (unbounded)
+fn next_major(major: u64) -> Option<SemVer> {
+    next_number(major).map(|ma| floor(ma, 0, 0))
+}
+
+fn next_minor(major: u64, minor: u64) -> Option<SemVer> {
+    next_number(minor).map(|mi| floor(major, mi, 0))
+}
+
+fn next_patch(major: u64, minor: u64, patch: u64) -> Option<SemVer> {
+    next_number(patch).map(|pa| floor(major, minor, pa))
+}
+
+fn bounded(lower: SemVer, upper: Option<SemVer>) -> Vec<Vec<Comparator>> {
+    let mut set = vec![Comparator::new(Op::Gte, lower)];
+    set.extend(upper.map(|u| Comparator::new(Op::Lt, u)));
+    vec![set]
+}
+
+fn any() -> Vec<Vec<Comparator>> {
+    vec![vec![]]
+}
+
+fn none() -> Vec<Vec<Comparator>> {
+    vec![]
+}
+
+fn primitive(op: Op, v: &Version) -> Vec<Vec<Comparator>> {
+    let p = Partial::from(Some(v));
+    if let Some(full) = p.complete() {
+        return vec![vec![Comparator::new(op, full)]];
+    }
+    let major = match p.major {
+        Some(major) => major,
+        // >* and <* match nothing, every other comparator matches anything
+        None => return match op {
+            Op::Lt | Op::Gt => none(),
+            _ => any(),
+        },
+    };
+    match (op, p.minor) {
+        // =1 and =1.2 are x-ranges
+        (Op::Eq, None) => bounded(SemVer::new(major, 0, 0), next_major(major)),
+        (Op::Eq, Some(minor)) => bounded(SemVer::new(major, minor, 0), next_minor(major, minor)),
+        // >1 => >=2.0.0, >1.2 => >=1.3.0
+        (Op::Gt, None) => match next_number(major) {
+            Some(ma) => vec![vec![Comparator::new(Op::Gte, SemVer::new(ma, 0, 0))]],
+            None => none(),
+        },
+        (Op::Gt, Some(minor)) => match next_number(minor) {
+            Some(mi) => vec![vec![Comparator::new(Op::Gte, SemVer::new(major, mi, 0))]],
+            None => next_number(major).map_or_else(none, |ma| {
+                vec![vec![Comparator::new(Op::Gte, SemVer::new(ma, 0, 0))]]
+            }),
+        },
+        // >=1.2 => >=1.2.0
+        (Op::Gte, minor) => {
+            vec![vec![Comparator::new(Op::Gte, SemVer::new(major, minor.unwrap_or(0), 0))]]
+        }
+        // <=1 => <2.0.0-0, <=1.2 => <1.3.0-0
+        (Op::Lte, minor) => {
+            let upper = match minor {
+                None => next_major(major),
+                Some(minor) => next_minor(major, minor),
+            };
+            upper.map_or_else(any, |u| vec![vec![Comparator::new(Op::Lt, u)]])
+        }
+        // <1.2 => <1.2.0-0
+        (Op::Lt, minor) => vec![vec![Comparator::new(Op::Lt, floor(major, minor.unwrap_or(0), 0))]],
+    }
+}
+
+fn tilde(v: &Version) -> Vec<Vec<Comparator>> {
+    // ~1.2.3 => >=1.2.3 <1.3.0-0, ~1.2 => >=1.2.0 <1.3.0-0, ~1 => >=1.0.0 <2.0.0-0
+    let p = Partial::from(Some(v));
+    match (p.major, p.minor, p.complete()) {
+        (None, _, _) => any(),
+        (Some(ma), None, _) => bounded(SemVer::new(ma, 0, 0), next_major(ma)),
+        (Some(ma), Some(mi), None) => bounded(SemVer::new(ma, mi, 0), next_minor(ma, mi)),
+        (Some(ma), Some(mi), Some(full)) => bounded(full, next_minor(ma, mi)),
+    }
+}
+
+fn caret(v: &Version) -> Vec<Vec<Comparator>> {
+    // allows changes that do not modify the left-most non-zero element
+    // ^1.2.3 => >=1.2.3 <2.0.0-0, ^0.2.3 => >=0.2.3 <0.3.0-0, ^0.0.3 => >=0.0.3 <0.0.4-0
+    let p = Partial::from(Some(v));
+    match (p.major, p.minor, p.patch, p.complete()) {
+        (None, _, _, _) => any(),
+        (Some(ma), None, _, _) => bounded(SemVer::new(ma, 0, 0), next_major(ma)),
+        (Some(0), Some(mi), None, _) => bounded(SemVer::new(0, mi, 0), next_minor(0, mi)),
+        (Some(ma), Some(mi), None, _) => bounded(SemVer::new(ma, mi, 0), next_major(ma)),
+        (Some(0), Some(0), Some(pa), Some(full)) => bounded(full, next_patch(0, 0, pa)),
+        (Some(0), Some(mi), Some(_), Some(full)) => bounded(full, next_minor(0, mi)),
+        (Some(ma), Some(_), Some(_), Some(full)) => bounded(full, next_major(ma)),
+        (Some(_), Some(_), Some(_), None) => unreachable!("complete() has all fields"),
+    }
+}
+
+fn hyphen(from: Option<&Version>, to: Option<&Version>) -> Vec<Vec<Comparator>> {
+    // 1.2 - 2.3.4 => >=1.2.0 <=2.3.4, 1.2.3 - 2.3 => >=1.2.3 <2.4.0-0
+    let (f, t) = (Partial::from(from), Partial::from(to));
+    let mut set = vec![];
+    match (f.major, f.minor, f.complete()) {
+        (None, _, _) => {}
+        (Some(ma), None, _) => set.push(Comparator::new(Op::Gte, SemVer::new(ma, 0, 0))),
+        (Some(ma), Some(mi), None) => set.push(Comparator::new(Op::Gte, SemVer::new(ma, mi, 0))),
+        (Some(_), Some(_), Some(full)) => set.push(Comparator::new(Op::Gte, full)),
+    }
+    match (t.major, t.minor, t.complete()) {
+        (None, _, _) => {}
+        (Some(ma), None, _) => set.extend(next_major(ma).map(|u| Comparator::new(Op::Lt, u))),
+        (Some(ma), Some(mi), None) => set.extend(next_minor(ma, mi).map(|u| Comparator::new(Op::Lt, u))),
+        (Some(_), Some(_), Some(full)) => set.push(Comparator::new(Op::Lte, full)),
+    }
+    vec![set]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "1.2.x-pre" style shorthand, the npm parser lives in drivers::npm
+    fn v(s: &str) -> Version {
+        let (core, pre_release) = match s.find('-') {
+            Some(i) => (&s[..i], Some(s[i + 1..].to_string())),
+            None => (s, None),
+        };
+        let mut fields = core.split('.').map(|f| match f {
+            "x" | "X" | "*" => SemVerField::Wildcard,
+            n => SemVerField::Number(n.parse().unwrap()),
+        });
+        Version::SemVer(SemVer {
+            major: fields.next().unwrap_or(SemVerField::Missing),
+            minor: fields.next().unwrap_or(SemVerField::Missing),
+            patch: fields.next().unwrap_or(SemVerField::Missing),
+            pre_release,
+            build: None,
+        })
+    }
+
+    fn sets(q: VersionQuery) -> Vec<Vec<(Op, Version)>> {
+        q.comparator_sets().into_iter()
+            .map(|set| set.into_iter().map(|c| (c.op, Version::SemVer(c.version))).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_desugar_caret() {
+        use VersionQuery::Compat;
+        assert_eq!(sets(Compat(v("1.2.3"))), vec![vec![(Op::Gte, v("1.2.3")), (Op::Lt, v("2.0.0-0"))]]);
+        assert_eq!(sets(Compat(v("0.2.3"))), vec![vec![(Op::Gte, v("0.2.3")), (Op::Lt, v("0.3.0-0"))]]);
+        assert_eq!(sets(Compat(v("0.0.3"))), vec![vec![(Op::Gte, v("0.0.3")), (Op::Lt, v("0.0.4-0"))]]);
+        assert_eq!(sets(Compat(v("0.0.x"))), vec![vec![(Op::Gte, v("0.0.0")), (Op::Lt, v("0.1.0-0"))]]);
+        assert_eq!(sets(Compat(v("0.x"))), vec![vec![(Op::Gte, v("0.0.0")), (Op::Lt, v("1.0.0-0"))]]);
+        assert_eq!(sets(Compat(v("1.2.3-beta.2"))), vec![vec![(Op::Gte, v("1.2.3-beta.2")), (Op::Lt, v("2.0.0-0"))]]);
+        assert_eq!(sets(Compat(v("*"))), vec![vec![]]);
+    }
+
+    #[test]
+    fn test_desugar_tilde_and_xrange() {
+        use VersionQuery::{Approx, Gt, Lte, Lt};
+        assert_eq!(sets(Approx(v("1.2"))), vec![vec![(Op::Gte, v("1.2.0")), (Op::Lt, v("1.3.0-0"))]]);
+        assert_eq!(sets(Approx(v("1"))), vec![vec![(Op::Gte, v("1.0.0")), (Op::Lt, v("2.0.0-0"))]]);
+        assert_eq!(sets(VersionQuery::Version(v("1.x"))), vec![vec![(Op::Gte, v("1.0.0")), (Op::Lt, v("2.0.0-0"))]]);
+        assert_eq!(sets(Gt(v("1.2"))), vec![vec![(Op::Gte, v("1.3.0"))]]);
+        assert_eq!(sets(Lte(v("1.2"))), vec![vec![(Op::Lt, v("1.3.0-0"))]]);
+        assert_eq!(sets(Lt(v("1.2"))), vec![vec![(Op::Lt, v("1.2.0-0"))]]);
+        assert_eq!(sets(Gt(v("*"))), Vec::<Vec<(Op, Version)>>::new());
+    }
+
+    #[test]
+    fn test_desugar_hyphen() {
+        let range = |a, b| VersionQuery::Range(
+            Box::new(VersionQuery::Version(v(a))),
+            Box::new(VersionQuery::Version(v(b))),
+        );
+        assert_eq!(sets(range("1.2", "2.3.4")), vec![vec![(Op::Gte, v("1.2.0")), (Op::Lte, v("2.3.4"))]]);
+        assert_eq!(sets(range("1.2.3", "2.3")), vec![vec![(Op::Gte, v("1.2.3")), (Op::Lt, v("2.4.0-0"))]]);
+        assert_eq!(sets(range("*", "2")), vec![vec![(Op::Lt, v("3.0.0-0"))]]);
+    }
+
+    #[test]
+    fn test_matches_not() {
+        use VersionQuery::{And, Compat, Not, Eq};
+        let q = And(Box::new(Compat(v("1.2.3"))), Box::new(Not(Box::new(Eq(v("1.2.5"))))));
+        assert!(q.matches(&v("1.2.4")));
+        assert!(!q.matches(&v("1.2.5")));
+        assert!(q.matches(&v("1.9.0")));
+        assert!(!q.matches(&v("2.0.0")));
+        assert!(!Not(Box::new(Compat(v("*")))).matches(&v("1.0.0")));
+    }
+
+    #[test]
+    fn test_matches_requires_complete_version() {
+        let q = VersionQuery::Compat(v("1"));
+        assert!(q.matches(&v("1.0.0")));
+        assert!(!q.matches(&v("1.x")));
+        assert!(!q.matches(&Version::Missing));
+    }
+
+    #[test]
+    fn test_matches_overflow_is_unbounded() {
+        let q = VersionQuery::Compat(v("9007199254740991.0.0"));
+        assert!(q.matches(&v("9007199254740991.9007199254740991.1")));
+        assert!(!VersionQuery::Gt(v("9007199254740991")).matches(&v("9007199254740991.1.0")));
+    }
+
+    #[test]
+    fn test_max_min_satisfying() {
+        use VersionQuery::{Compat, Gte, Or};
+        let published: Vec<Version> = [
+            "0.9.0", "1.0.0", "1.2.3", "1.4.0-beta.1", "1.4.0", "1.9.9", "2.0.0-rc.1", "2.0.0", "3.0.0",
+        ].iter().map(|s| v(s)).collect();
+        let q = Compat(v("1.2"));
+        assert_eq!(q.max_satisfying(&published), Some(&v("1.9.9")));
+        assert_eq!(q.min_satisfying(&published), Some(&v("1.2.3")));
+        // a loose lower bound lets in every new major
+        assert_eq!(Gte(v("1")).max_satisfying(&published), Some(&v("3.0.0")));
+        assert_eq!(Compat(v("4")).max_satisfying(&published), None);
+        // pre-releases need an opt-in on the same [major, minor, patch]
+        let q = Or(Box::new(Compat(v("1.4.0-beta.0"))), Box::new(Compat(v("5"))));
+        assert_eq!(q.min_satisfying(&published), Some(&v("1.4.0-beta.1")));
+        assert_eq!(Compat(v("2.0.0-rc.0")).min_satisfying(&published), Some(&v("2.0.0-rc.1")));
+        assert_eq!(Compat(v("1.9")).max_satisfying(&published), Some(&v("1.9.9")));
+        assert_eq!(Gte(v("1.9.9")).min_satisfying(&published), Some(&v("1.9.9")));
+    }
+
+    #[test]
+    fn test_matches_numver() {
+        use crate::version::NumVer;
+        use VersionQuery::{And, Gte, Lt};
+        let nv = |s| Version::NumVer(NumVer::parse(s).unwrap());
+        let q = And(Box::new(Gte(nv("1.2.3.4"))), Box::new(Lt(nv("1.3"))));
+        assert!(q.matches(&nv("1.2.10")));
+        assert!(!q.matches(&nv("1.2.3")));
+        assert!(!q.matches(&Version::StrVer("1.2.10".to_string())));
+    }
+
+    #[test]
+    fn test_matches_calver() {
+        use crate::version::CalVer;
+        use VersionQuery::{And, Compat, Gte, Lt};
+        let cv = |s| Version::CalVer(CalVer::parse("YYYY.0M.0D", s).unwrap());
+        let q = And(Box::new(Gte(cv("2020.01.01"))), Box::new(Lt(cv("2021.01.01"))));
+        assert!(q.matches(&cv("2020.06.07")));
+        assert!(!q.matches(&cv("2021.02.01")));
+        assert!(!q.matches(&v("2020.6.7")));
+        assert!(!Compat(cv("2020.01.01")).matches(&cv("2020.01.02")));
+        assert!(!Gte(v("1.0.0")).matches(&cv("2020.01.02")));
+        let published = vec![cv("2019.12.31"), v("1.0.0"), cv("2020.06.07"), cv("2021.01.01")];
+        assert_eq!(q.max_satisfying(&published), Some(&cv("2020.06.07")));
+        assert!(q.to_intervals().is_empty());
+    }
+}
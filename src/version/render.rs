@@ -0,0 +1,536 @@
+// Rendering a VersionQuery back to an ecosystem's range syntax
+//   npm       https://docs.npmjs.com/misc/semver#ranges
+//   Cargo     https://doc.rust-lang.org/cargo/reference/specifying-dependencies.html
+//   PEP 440   https://www.python.org/dev/peps/pep-0440/#version-specifiers
+//   Maven     https://maven.apache.org/enforcer/enforcer-rules/versionRanges.html
+//   RubyGems  https://guides.rubygems.org/patterns/#declaring-dependencies
+//
+// Queries keep their npm shape where the target has the same operator
+// (^1.2.3 stays ^1.2.3 in Cargo), anything else goes through the
+// desugared comparators or the interval form of the query.
+use std::error::Error;
+use std::fmt;
+
+use super::interval::{successor, Interval};
+use super::range::{Comparator, Op};
+use super::{Ecosystem, SemVer, SemVerField, Version, VersionQuery};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderError {
+    Unsatisfiable,                  // the query matches no version
+    Disjunction(Ecosystem),         // the ecosystem has no "||"
+    PreRelease(Ecosystem, String),  // pre-release tag without an equivalent
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenderError::Unsatisfiable => write!(f, "range matches no version"),
+            RenderError::Disjunction(e) => write!(f, "{} ranges cannot express a union of disjoint ranges", e),
+            RenderError::PreRelease(e, pre) => write!(f, "pre-release \"{}\" has no {} equivalent", pre, e),
+        }
+    }
+}
+
+impl Error for RenderError {}
+
+impl fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Ecosystem::Npm => "npm",
+            Ecosystem::Cargo => "Cargo",
+            Ecosystem::Pep440 => "PEP 440",
+            Ecosystem::Maven => "Maven",
+            Ecosystem::RubyGems => "RubyGems",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Version::SemVer(v) => write!(f, "{}", v),
+            Version::CalVer(v) => write!(f, "{}", v),
+            Version::NumVer(v) => write!(f, "{}", v),
+            Version::StrVer(v) => write!(f, "{}", v),
+            Version::Missing => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for VersionQuery {
+    // npm syntax, which can express every query
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if is_npm_shaped(self, false) {
+            write_npm(f, self)
+        } else {
+            let sets = self.comparator_sets();
+            if sets.is_empty() {
+                return write!(f, "<0.0.0-0");
+            }
+            for (n, set) in sets.iter().enumerate() {
+                if n > 0 {
+                    write!(f, " || ")?;
+                }
+                if set.is_empty() {
+                    write!(f, "*")?;
+                }
+                for (m, c) in set.iter().enumerate() {
+                    if m > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}{}", op_symbol(c.op), c.version)?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+impl VersionQuery {
+    pub fn render(&self, ecosystem: Ecosystem) -> Result<String, RenderError> {
+        match ecosystem {
+            Ecosystem::Npm => Ok(self.to_string()),
+            Ecosystem::Maven => render_maven(self),
+            Ecosystem::Cargo | Ecosystem::Pep440 | Ecosystem::RubyGems => {
+                render_conjunction(self, ecosystem)
+            }
+        }
+    }
+}
+
+// npm has no "!" and no parentheses, so "||" may only appear above " "
+fn is_npm_shaped(q: &VersionQuery, in_and: bool) -> bool {
+    use VersionQuery::*;
+    match q {
+        Or(lhs, rhs) => !in_and && is_npm_shaped(lhs, false) && is_npm_shaped(rhs, false),
+        And(lhs, rhs) => is_npm_shaped(lhs, true) && is_npm_shaped(rhs, true),
+        Range(from, to) => matches!((&**from, &**to), (Version(_), Version(_))),
+        Not(_) => false,
+        _ => true,
+    }
+}
+
+fn write_npm(f: &mut fmt::Formatter, q: &VersionQuery) -> fmt::Result {
+    use VersionQuery::*;
+    match q {
+        And(lhs, rhs) => {
+            write_npm(f, lhs)?;
+            write!(f, " ")?;
+            write_npm(f, rhs)
+        }
+        Or(lhs, rhs) => {
+            write_npm(f, lhs)?;
+            write!(f, " || ")?;
+            write_npm(f, rhs)
+        }
+        Range(from, to) => {
+            write_npm(f, from)?;
+            write!(f, " - ")?;
+            write_npm(f, to)
+        }
+        Not(q) => {
+            write!(f, "!")?;
+            write_npm(f, q)
+        }
+        Lt(v) => write!(f, "<{}", v),
+        Lte(v) => write!(f, "<={}", v),
+        Gt(v) => write!(f, ">{}", v),
+        Gte(v) => write!(f, ">={}", v),
+        Eq(v) => write!(f, "={}", v),
+        Approx(v) => write!(f, "~{}", v),
+        Compat(v) => write!(f, "^{}", v),
+        Version(v) => write!(f, "{}", v),
+    }
+}
+
+fn op_symbol(op: Op) -> &'static str {
+    match op {
+        Op::Lt => "<",
+        Op::Lte => "<=",
+        Op::Gt => ">",
+        Op::Gte => ">=",
+        Op::Eq => "=",
+    }
+}
+
+// Cargo, PEP 440 and RubyGems only have comma separated comparators
+fn render_conjunction(q: &VersionQuery, eco: Ecosystem) -> Result<String, RenderError> {
+    // whether or not a single part is empty, as with >2 <1
+    if q.to_intervals().is_empty() {
+        return Err(RenderError::Unsatisfiable);
+    }
+    let mut parts = vec![];
+    for node in flatten_and(q) {
+        if let Some(s) = render_leaf(node, eco)? {
+            parts.push(s);
+            continue;
+        }
+        let sets = node.comparator_sets();
+        match sets.as_slice() {
+            [set] => {
+                for c in set.iter() {
+                    parts.push(render_comparator(c.op, &c.version, eco)?);
+                }
+            }
+            _ => return render_interval(q, eco),
+        }
+    }
+    if parts.is_empty() {
+        return Ok(any(eco).to_string());
+    }
+    Ok(parts.join(", "))
+}
+
+fn flatten_and(q: &VersionQuery) -> Vec<&VersionQuery> {
+    match q {
+        VersionQuery::And(lhs, rhs) => {
+            let mut nodes = flatten_and(lhs);
+            nodes.extend(flatten_and(rhs));
+            nodes
+        }
+        _ => vec![q],
+    }
+}
+
+fn any(eco: Ecosystem) -> &'static str {
+    match eco {
+        Ecosystem::Npm | Ecosystem::Cargo => "*",
+        Ecosystem::Pep440 => ">=0",
+        Ecosystem::RubyGems => ">= 0",
+        Ecosystem::Maven => "[0,)",
+    }
+}
+
+// falls back to the whole query's interval form, which must be a single
+// interval since there is no way to write a union
+fn render_interval(q: &VersionQuery, eco: Ecosystem) -> Result<String, RenderError> {
+    let set = q.to_intervals();
+    match set.intervals() {
+        [] => Err(RenderError::Unsatisfiable),
+        [Interval { lower: None, upper: None }] => Ok(any(eco).to_string()),
+        [i] => {
+            if let Some(v) = exact(i) {
+                return render_comparator(Op::Eq, v, eco);
+            }
+            let comparators: Vec<_> = q.comparator_sets().into_iter().flatten().collect();
+            let mut parts = vec![];
+            if let Some(l) = &i.lower {
+                let (op, v) = lower_bound(l, &comparators);
+                parts.push(render_comparator(op, v, eco)?);
+            }
+            if let Some(u) = &i.upper {
+                let (op, v) = upper_bound(u, &comparators);
+                parts.push(render_comparator(op, v, eco)?);
+            }
+            Ok(parts.join(", "))
+        }
+        _ => Err(RenderError::Disjunction(eco)),
+    }
+}
+
+// [v, successor(v)) only admits v
+fn exact(i: &Interval) -> Option<&SemVer> {
+    match (&i.lower, &i.upper) {
+        (Some(l), Some(u)) if successor(l).as_ref() == Some(u) => Some(l),
+        _ => None,
+    }
+}
+
+// The bounds of an interval as the comparator that set them wrote them, so
+// >1.2.3 stays exclusive rather than turning into >=1.2.4-0 and <=1.2.3
+// inclusive rather than <1.2.4-0.
+fn lower_bound<'a>(l: &'a SemVer, comparators: &'a [Comparator]) -> (Op, &'a SemVer) {
+    comparators.iter()
+        .find(|c| c.op == Op::Gt && successor(&c.version).as_ref() == Some(l))
+        .map_or((Op::Gte, l), |c| (Op::Gt, &c.version))
+}
+
+fn upper_bound<'a>(u: &'a SemVer, comparators: &'a [Comparator]) -> (Op, &'a SemVer) {
+    comparators.iter()
+        .find(|c| c.op == Op::Lte && successor(&c.version).as_ref() == Some(u))
+        .map_or((Op::Lt, u), |c| (Op::Lte, &c.version))
+}
+
+fn render_maven(q: &VersionQuery) -> Result<String, RenderError> {
+    let set = q.to_intervals();
+    if set.is_empty() {
+        return Err(RenderError::Unsatisfiable);
+    } else if set.is_full() {
+        return Ok(any(Ecosystem::Maven).to_string());
+    }
+    let comparators: Vec<_> = q.comparator_sets().into_iter().flatten().collect();
+    let mut parts = vec![];
+    for i in set.intervals() {
+        if let Some(v) = exact(i) {
+            parts.push(format!("[{}]", render_version(v, Ecosystem::Maven)?));
+            continue;
+        }
+        let lower = match &i.lower {
+            Some(l) => match lower_bound(l, &comparators) {
+                (Op::Gt, v) => format!("({}", render_version(v, Ecosystem::Maven)?),
+                (_, v) => format!("[{}", render_version(v, Ecosystem::Maven)?),
+            },
+            None => "(".to_string(),
+        };
+        let upper = match &i.upper {
+            Some(u) => match upper_bound(u, &comparators) {
+                (Op::Lte, v) => format!("{}]", render_version(v, Ecosystem::Maven)?),
+                (_, v) => format!("{})", render_version(&strip_floor(v), Ecosystem::Maven)?),
+            },
+            None => ")".to_string(),
+        };
+        parts.push(format!("{},{}", lower, upper));
+    }
+    Ok(parts.join(","))
+}
+
+// <2.0.0-0 is how npm spells "below every 2.0.0 pre-release", the other
+// ecosystems already exclude pre-releases of an exclusive upper bound
+fn strip_floor(v: &SemVer) -> SemVer {
+    let mut v = v.clone();
+    if v.pre_release.as_deref() == Some("0") {
+        v.pre_release = None;
+    }
+    v
+}
+
+fn render_comparator(op: Op, v: &SemVer, eco: Ecosystem) -> Result<String, RenderError> {
+    let (op, v) = match op {
+        Op::Lt => ("<", strip_floor(v)),
+        Op::Lte => ("<=", v.clone()),
+        Op::Gt => (">", v.clone()),
+        Op::Gte => (">=", v.clone()),
+        Op::Eq => match eco {
+            Ecosystem::Pep440 => ("==", v.clone()),
+            _ => ("=", v.clone()),
+        },
+    };
+    let v = render_version(&v, eco)?;
+    Ok(match eco {
+        Ecosystem::RubyGems => format!("{} {}", op, v),
+        _ => format!("{}{}", op, v),
+    })
+}
+
+// The numeric fields before the first x, * or missing one
+fn numeric_prefix(v: &SemVer) -> Vec<u64> {
+    [v.major, v.minor, v.patch].iter()
+        .map_while(|f| match f {
+            SemVerField::Number(n) => Some(*n),
+            _ => None,
+        })
+        .collect()
+}
+
+fn join(numbers: &[u64]) -> String {
+    numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(".")
+}
+
+fn render_version(v: &SemVer, eco: Ecosystem) -> Result<String, RenderError> {
+    let mut s = join(&numeric_prefix(v));
+    if let Some(pre) = &v.pre_release {
+        match eco {
+            Ecosystem::Pep440 => s.push_str(&pep440_pre_release(pre)?),
+            // a version is a pre-release in RubyGems only when it has a
+            // letter in it, 1.0.0.0 is a release
+            Ecosystem::RubyGems if !pre.bytes().any(|c| c.is_ascii_alphabetic()) => {
+                return Err(RenderError::PreRelease(Ecosystem::RubyGems, pre.to_string()));
+            }
+            Ecosystem::RubyGems => {
+                s.push('.');
+                s.push_str(&pre.replace('-', "."));
+            }
+            _ => {
+                s.push('-');
+                s.push_str(pre);
+            }
+        }
+    }
+    if let (Ecosystem::Npm, Some(build)) = (eco, &v.build) {
+        s.push('+');
+        s.push_str(build);
+    }
+    Ok(s)
+}
+
+// alpha.1 => a1, beta => b0, rc.2 => rc2, 0 => .dev0
+fn pep440_pre_release(pre: &str) -> Result<String, RenderError> {
+    let mut ids = pre.split('.');
+    let tag = ids.next().unwrap_or("");
+    let (tag, number) = match ids.next() {
+        Some(n) if n.bytes().all(|c| c.is_ascii_digit()) => (tag, n),
+        None => {
+            // alpha1 style
+            let digits = tag.find(|c: char| c.is_ascii_digit()).unwrap_or(tag.len());
+            (&tag[..digits], if digits < tag.len() { &tag[digits..] } else { "0" })
+        }
+        Some(_) => return Err(RenderError::PreRelease(Ecosystem::Pep440, pre.to_string())),
+    };
+    if ids.next().is_some() {
+        return Err(RenderError::PreRelease(Ecosystem::Pep440, pre.to_string()));
+    }
+    let number: u64 = number.parse()
+        .map_err(|_| RenderError::PreRelease(Ecosystem::Pep440, pre.to_string()))?;
+    let tag = match tag {
+        "a" | "alpha" => "a",
+        "b" | "beta" => "b",
+        "c" | "rc" | "pre" | "preview" => "rc",
+        "" | "dev" => ".dev",
+        _ => return Err(RenderError::PreRelease(Ecosystem::Pep440, pre.to_string())),
+    };
+    Ok(format!("{}{}", tag, number))
+}
+
+fn leaf_semver(q: &VersionQuery) -> Option<&SemVer> {
+    use VersionQuery::*;
+    match q {
+        Lt(v) | Lte(v) | Gt(v) | Gte(v) | Eq(v) | Approx(v) | Compat(v) | Version(v) => match v {
+            super::Version::SemVer(v) => Some(v),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// The leaves with a direct counterpart, None when the comparators have
+// to be spelled out instead.
+fn render_leaf(q: &VersionQuery, eco: Ecosystem) -> Result<Option<String>, RenderError> {
+    let v = match leaf_semver(q) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let complete = v.is_complete();
+    use VersionQuery::*;
+    let prefix = numeric_prefix(v);
+    if prefix.is_empty() {
+        return Ok(None); // "*" and friends
+    }
+    let rendered = match (eco, q) {
+        // Cargo shares npm's meaning for every operator, except that a bare
+        // version is a caret requirement
+        (Ecosystem::Cargo, Version(_)) if complete => format!("={}", render_version(v, eco)?),
+        (Ecosystem::Cargo, Version(_)) | (Ecosystem::Cargo, Eq(_)) if !complete => {
+            format!("{}.*", join(&prefix))
+        }
+        (Ecosystem::Cargo, _) => {
+            let op = match q {
+                Lt(_) => "<",
+                Lte(_) => "<=",
+                Gt(_) => ">",
+                Gte(_) => ">=",
+                Eq(_) => "=",
+                Approx(_) => "~",
+                Compat(_) => "^",
+                _ => unreachable!(),
+            };
+            format!("{}{}", op, render_version(v, eco)?)
+        }
+        (Ecosystem::Pep440, Approx(_)) if complete => format!("~={}", render_version(v, eco)?),
+        (Ecosystem::Pep440, Approx(_)) | (Ecosystem::Pep440, Version(_)) | (Ecosystem::Pep440, Eq(_))
+            if !complete => format!("=={}.*", join(&prefix)),
+        // ~> drops the last given number: ~> 1.2.3 is >= 1.2.3, < 1.3
+        (Ecosystem::RubyGems, Approx(_)) => match prefix.as_slice() {
+            [ma] => format!("~> {}.0", ma),
+            [ma, mi] => format!("~> {}.{}.0", ma, mi),
+            _ => format!("~> {}", render_version(v, eco)?),
+        },
+        (Ecosystem::RubyGems, Compat(_)) if v.pre_release.is_none() => match prefix.as_slice() {
+            [ma] | [ma, 0] | [ma, 0, 0] if *ma > 0 => format!("~> {}.0", ma),
+            [ma, mi] if *ma > 0 => format!("~> {}.{}", ma, mi),
+            [0, mi] | [0, mi, 0] if *mi > 0 => format!("~> 0.{}.0", mi),
+            [0, mi, pa] if *mi > 0 => format!("~> 0.{}.{}", mi, pa),
+            _ => return Ok(None),
+        },
+        (_, Version(_)) | (_, Eq(_)) | (_, Lt(_)) | (_, Lte(_)) | (_, Gt(_)) | (_, Gte(_)) if complete => {
+            let op = match q {
+                Lt(_) => Op::Lt,
+                Lte(_) => Op::Lte,
+                Gt(_) => Op::Gt,
+                Gte(_) => Op::Gte,
+                _ => Op::Eq,
+            };
+            return render_comparator(op, v, eco).map(Some);
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(rendered))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::npm_semver::parse_range;
+
+    fn render(range: &str, eco: Ecosystem) -> Result<String, RenderError> {
+        parse_range(range).unwrap().render(eco)
+    }
+
+    #[test]
+    fn test_display_fallback() {
+        use VersionQuery::{And, Not, Or};
+        let q = |r| Box::new(parse_range(r).unwrap());
+        let and_of_or = And(Box::new(Or(q("1.x"), q("3.x"))), q("<3.5"));
+        assert_eq!(and_of_or.to_string(), ">=1.0.0 <2.0.0-0 <3.5.0-0 || >=3.0.0 <4.0.0-0 <3.5.0-0");
+        assert_eq!(Not(q("*")).to_string(), "<0.0.0-0");
+        assert_eq!(Not(q("<1.2.3")).to_string(), ">=1.2.3");
+    }
+
+    #[test]
+    fn test_render_cargo() {
+        assert_eq!(render("^1.2.3", Ecosystem::Cargo).unwrap(), "^1.2.3");
+        assert_eq!(render("~1.2", Ecosystem::Cargo).unwrap(), "~1.2");
+        assert_eq!(render("1.2.3", Ecosystem::Cargo).unwrap(), "=1.2.3");
+        assert_eq!(render("1.2.x", Ecosystem::Cargo).unwrap(), "1.2.*");
+        assert_eq!(render(">=1.2.3 <2.0", Ecosystem::Cargo).unwrap(), ">=1.2.3, <2.0");
+        assert_eq!(render("1.2.3 - 2.3", Ecosystem::Cargo).unwrap(), ">=1.2.3, <2.4.0");
+        assert_eq!(render("^1.2.3 || ^1.4", Ecosystem::Cargo).unwrap(), ">=1.2.3, <2.0.0");
+        assert_eq!(render("*", Ecosystem::Cargo).unwrap(), "*");
+        assert_eq!(render("^1 || ^3", Ecosystem::Cargo), Err(RenderError::Disjunction(Ecosystem::Cargo)));
+        assert_eq!(render(">2 <1", Ecosystem::Cargo), Err(RenderError::Unsatisfiable));
+        assert_eq!(render("<*", Ecosystem::Cargo), Err(RenderError::Unsatisfiable));
+        assert_eq!(render(">=1.2.3 <1.2.3", Ecosystem::Pep440), Err(RenderError::Unsatisfiable));
+        assert_eq!(render("^2 <1", Ecosystem::RubyGems), Err(RenderError::Unsatisfiable));
+    }
+
+    #[test]
+    fn test_render_pep440() {
+        assert_eq!(render("^1.2.3", Ecosystem::Pep440).unwrap(), ">=1.2.3, <2.0.0");
+        assert_eq!(render("~1.2.3", Ecosystem::Pep440).unwrap(), "~=1.2.3");
+        assert_eq!(render("~1.2", Ecosystem::Pep440).unwrap(), "==1.2.*");
+        assert_eq!(render("1.2.3-rc.1", Ecosystem::Pep440).unwrap(), "==1.2.3rc1");
+        assert_eq!(render(">=1.0.0-alpha", Ecosystem::Pep440).unwrap(), ">=1.0.0a0");
+        assert_eq!(render("*", Ecosystem::Pep440).unwrap(), ">=0");
+        assert_eq!(render("1.0.0-foo.bar", Ecosystem::Pep440),
+                   Err(RenderError::PreRelease(Ecosystem::Pep440, "foo.bar".to_string())));
+    }
+
+    #[test]
+    fn test_render_maven() {
+        assert_eq!(render("^1.2.3", Ecosystem::Maven).unwrap(), "[1.2.3,2.0.0)");
+        assert_eq!(render("1.2.3", Ecosystem::Maven).unwrap(), "[1.2.3]");
+        assert_eq!(render("<1.0.0 || >=1.2", Ecosystem::Maven).unwrap(), "(,1.0.0),[1.2.0,)");
+        assert_eq!(render("*", Ecosystem::Maven).unwrap(), "[0,)");
+        // exclusive lower and inclusive upper bounds keep their brackets
+        assert_eq!(render(">1.2.3", Ecosystem::Maven).unwrap(), "(1.2.3,)");
+        assert_eq!(render("<=1.2.3", Ecosystem::Maven).unwrap(), "(,1.2.3]");
+        assert_eq!(render(">1.2.3 <=2.0.0", Ecosystem::Maven).unwrap(), "(1.2.3,2.0.0]");
+        assert_eq!(render("1.2.3 - 1.4.0", Ecosystem::Maven).unwrap(), "[1.2.3,1.4.0]");
+    }
+
+    #[test]
+    fn test_render_rubygems() {
+        assert_eq!(render("~1.2", Ecosystem::RubyGems).unwrap(), "~> 1.2.0");
+        assert_eq!(render("~1.2.3", Ecosystem::RubyGems).unwrap(), "~> 1.2.3");
+        assert_eq!(render("^1.2", Ecosystem::RubyGems).unwrap(), "~> 1.2");
+        assert_eq!(render("^1", Ecosystem::RubyGems).unwrap(), "~> 1.0");
+        assert_eq!(render("^0.2.3", Ecosystem::RubyGems).unwrap(), "~> 0.2.3");
+        assert_eq!(render("^1.2.3", Ecosystem::RubyGems).unwrap(), ">= 1.2.3, < 2.0.0");
+        assert_eq!(render(">=1.2.3-beta.1 <2", Ecosystem::RubyGems).unwrap(), ">= 1.2.3.beta.1, < 2.0.0");
+        assert_eq!(render("*", Ecosystem::RubyGems).unwrap(), ">= 0");
+        assert_eq!(render(">1.2.3 || >1.5", Ecosystem::RubyGems).unwrap(), "> 1.2.3");
+        assert_eq!(render(">=1.0.0-0", Ecosystem::RubyGems),
+                   Err(RenderError::PreRelease(Ecosystem::RubyGems, "0".to_string())));
+    }
+}
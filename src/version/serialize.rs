@@ -0,0 +1,257 @@
+// Serde support. Versions and queries serialize as the strings they are
+// written as:
+//   "1.2.3-alpha+001", "^1.2.3 || 2.x"
+//
+// Reading them back goes through Version::classify and the npm range
+// parser, which can read a value as something else: a partial SemVer "1.2"
+// comes back as a NumVer, a query over non-semver versions does not parse
+// at all. Tooling that needs the exact value can ask for the lossless
+// structured form field by field:
+//   #[serde(with = "VersionQueryDef")] query: VersionQuery,
+//   => {"and": [{"gte": {"semver": {"major": {"number": 1}, ...}}}, ...]}
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use super::{CalVer, NumVer, SemVer, SemVerField, Version, VersionQuery};
+use super::npm_semver::{parse_partial, parse_range};
+
+impl Serialize for SemVer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl Serialize for VersionQuery {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// Deserializes a string with the given parser, `expecting` reads as the end
+// of "invalid value: string \"..\", expected .."
+struct StrVisitor<F> {
+    expecting: &'static str,
+    parse: F,
+}
+
+impl<'de, T, F: FnOnce(&str) -> Option<T>> Visitor<'de> for StrVisitor<F> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<T, E> {
+        let expecting = self.expecting;
+        (self.parse)(s).ok_or_else(|| E::invalid_value(de::Unexpected::Str(s), &expecting))
+    }
+}
+
+impl<'de> Deserialize<'de> for SemVer {
+    // full versions or partial ones as npm writes them: "1.2.3", "1.x"
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(StrVisitor {
+            expecting: "a semantic version",
+            parse: |s: &str| s.parse().or_else(|_| parse_partial(s)).ok(),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(StrVisitor {
+            expecting: "a version",
+            parse: |s: &str| Some(Version::classify(s)),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionQuery {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(StrVisitor {
+            expecting: "an npm version range",
+            parse: |s: &str| parse_range(s).ok(),
+        })
+    }
+}
+
+// The structured form, as remote definitions of the real types so that
+// any field can opt in with #[serde(with = "...Def")].
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SemVerField", rename_all = "lowercase")]
+enum SemVerFieldDef {
+    Number(u64),
+    Wildcard,
+    Missing,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SemVer")]
+pub struct SemVerDef {
+    #[serde(with = "SemVerFieldDef")]
+    major: SemVerField,
+    #[serde(with = "SemVerFieldDef")]
+    minor: SemVerField,
+    #[serde(with = "SemVerFieldDef")]
+    patch: SemVerField,
+    pre_release: Option<String>,
+    build: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "CalVer")]
+struct CalVerDef {
+    format: String,
+    year: u16,
+    month: Option<u8>,
+    week: Option<u8>,
+    day: Option<u8>,
+    major: Option<u32>,
+    minor: Option<u32>,
+    micro: Option<u32>,
+    build: Option<String>,
+    pre_release: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "NumVer")]
+struct NumVerDef {
+    numbers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Version", rename_all = "lowercase")]
+pub enum VersionDef {
+    SemVer(#[serde(with = "SemVerDef")] SemVer),
+    CalVer(#[serde(with = "CalVerDef")] CalVer),
+    NumVer(#[serde(with = "NumVerDef")] NumVer),
+    StrVer(String),
+    Missing,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "VersionQuery", rename_all = "lowercase")]
+pub enum VersionQueryDef {
+    And(#[serde(with = "boxed")] Box<VersionQuery>, #[serde(with = "boxed")] Box<VersionQuery>),
+    Or(#[serde(with = "boxed")] Box<VersionQuery>, #[serde(with = "boxed")] Box<VersionQuery>),
+    Range(#[serde(with = "boxed")] Box<VersionQuery>, #[serde(with = "boxed")] Box<VersionQuery>),
+    Not(#[serde(with = "boxed")] Box<VersionQuery>),
+    Lt(#[serde(with = "VersionDef")] Version),
+    Lte(#[serde(with = "VersionDef")] Version),
+    Gt(#[serde(with = "VersionDef")] Version),
+    Gte(#[serde(with = "VersionDef")] Version),
+    Eq(#[serde(with = "VersionDef")] Version),
+    Approx(#[serde(with = "VersionDef")] Version),
+    Compat(#[serde(with = "VersionDef")] Version),
+    Version(#[serde(with = "VersionDef")] Version),
+}
+
+// the structured form of the operands of And, Or, Range and Not
+mod boxed {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(q: &VersionQuery, serializer: S) -> Result<S::Ok, S::Error> {
+        VersionQueryDef::serialize(q, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Box<VersionQuery>, D::Error> {
+        VersionQueryDef::deserialize(deserializer).map(Box::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Structured {
+        #[serde(with = "VersionDef")]
+        version: Version,
+        #[serde(with = "VersionQueryDef")]
+        query: VersionQuery,
+    }
+
+    #[test]
+    fn test_string_form() {
+        let v = Version::classify("1.2.3-alpha+001");
+        assert_eq!(serde_json::to_string(&v).unwrap(), r#""1.2.3-alpha+001""#);
+        assert_eq!(serde_json::from_str::<Version>(r#""1.2.3-alpha+001""#).unwrap(), v);
+        assert_eq!(serde_json::from_str::<Version>(r#""2020.06.07""#).unwrap().to_string(), "2020.06.07");
+
+        let q = parse_range("^1.2.3 || 2.x").unwrap();
+        assert_eq!(serde_json::to_value(&q).unwrap(), json!("^1.2.3 || 2.x"));
+        assert_eq!(serde_json::from_value::<VersionQuery>(json!("^1.2.3 || 2.x")).unwrap(), q);
+        let err = serde_json::from_value::<VersionQuery>(json!("^1.2.3 foo")).unwrap_err();
+        assert_eq!(err.to_string(), r#"invalid value: string "^1.2.3 foo", expected an npm version range"#);
+
+        let v: SemVer = serde_json::from_value(json!("1.x")).unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (SemVerField::Number(1), SemVerField::Wildcard, SemVerField::Missing));
+        assert_eq!(serde_json::to_value(&v).unwrap(), json!("1.x"));
+        assert!(serde_json::from_value::<SemVer>(json!(">1.2.3")).is_err());
+        assert!(serde_json::from_value::<SemVer>(json!(123)).is_err());
+    }
+
+    #[test]
+    fn test_string_form_lossy() {
+        // anything the crate builds writes out as it displays, even when it
+        // would read back as something else
+        let partial = Version::SemVer(parse_partial("1.2").unwrap());
+        assert_eq!(serde_json::to_value(&partial).unwrap(), json!("1.2"));
+        let calver = Version::CalVer(CalVer::parse("YY.0M.MICRO", "20.04.1").unwrap());
+        assert_eq!(serde_json::to_value(&calver).unwrap(), json!("20.04.1"));
+        let q = VersionQuery::Lt(Version::classify("1.0-SNAPSHOT"));
+        assert_eq!(serde_json::to_value(&q).unwrap(), json!("<1.0-SNAPSHOT"));
+        let dep = crate::Dep { name: "a".to_string(), version: Some(q), spec: "<1.0-SNAPSHOT".to_string() };
+        assert_eq!(serde_json::to_value(&dep).unwrap()["version"], json!("<1.0-SNAPSHOT"));
+
+        for s in ["1.2.3", "2020.06.07", "1.02.3.4", "1.0-SNAPSHOT", ""].iter() {
+            let v = Version::classify(s);
+            assert_eq!(serde_json::from_str::<Version>(&serde_json::to_string(&v).unwrap()).unwrap(), v);
+        }
+    }
+
+    #[test]
+    fn test_structured_form() {
+        let value = json!({
+            "version": {"numver": {"numbers": ["1", "02"]}},
+            "query": {"or": [
+                {"gte": {"semver": {
+                    "major": {"number": 1}, "minor": "wildcard", "patch": "missing",
+                    "pre_release": null, "build": null,
+                }}},
+                {"not": {"compat": {"strver": "latest"}}},
+            ]},
+        });
+        let s: Structured = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(s.version.to_string(), "1.02");
+        match &s.query {
+            VersionQuery::Or(_, rhs) => assert_eq!(**rhs, VersionQuery::Not(Box::new(
+                VersionQuery::Compat(Version::StrVer("latest".to_string()))))),
+            q => panic!("expected an Or, got {:?}", q),
+        }
+        assert_eq!(serde_json::to_value(&s).unwrap(), value);
+
+        // everything the string form forgets survives
+        let s = Structured {
+            version: Version::CalVer(CalVer::parse("YY.0M.MICRO", "20.04.1").unwrap()),
+            query: VersionQuery::Lt(Version::classify("1.0-SNAPSHOT")),
+        };
+        let json = serde_json::to_string(&s).unwrap();
+        let back: Structured = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, s);
+        match back.version {
+            Version::CalVer(v) => assert_eq!(v.format, "YY.0M.MICRO"),
+            v => panic!("expected a CalVer, got {:?}", v),
+        }
+    }
+}
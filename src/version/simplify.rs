@@ -0,0 +1,132 @@
+// Shortest query admitting the same published versions, like node-semver's
+// simplifyRange() but also trying ^, ~ and x-ranges:
+//   ">=1.2.3 <2.0.0 || 1.5.x || >=1.9.0 <2.0.0" => "^1.2.3"
+//
+// Equivalence is only judged on the given versions, the result may well
+// differ from the original on versions that have not been published.
+use super::{SemVer, SemVerField, Version, VersionQuery};
+
+impl VersionQuery {
+    pub fn simplify(&self, published: &[Version]) -> VersionQuery {
+        let mut versions: Vec<&Version> = published.iter().collect();
+        versions.sort();
+        versions.dedup();
+        let included: Vec<bool> = versions.iter().map(|v| self.matches(v)).collect();
+
+        // runs of consecutive matching versions, as inclusive indices
+        let mut runs = vec![];
+        let mut i = 0;
+        while i < versions.len() {
+            if included[i] {
+                let start = i;
+                while i < versions.len() && included[i] {
+                    i += 1;
+                }
+                runs.push((start, i - 1));
+            } else {
+                i += 1;
+            }
+        }
+
+        let parts: Option<Vec<VersionQuery>> = runs.iter()
+            .map(|&(start, end)| {
+                let first = start == 0;
+                let last = end + 1 == versions.len();
+                candidates(versions[start], versions[end], first, last).into_iter()
+                    .filter(|q| {
+                        versions.iter().enumerate()
+                            .all(|(j, v)| q.matches(v) == (start <= j && j <= end))
+                    })
+                    .min_by_key(|q| q.to_string().len())
+            })
+            .collect();
+        let simplified = parts.and_then(|parts| {
+            parts.into_iter().reduce(|lhs, rhs| VersionQuery::Or(Box::new(lhs), Box::new(rhs)))
+        });
+        match simplified {
+            Some(q) if q.to_string().len() < self.to_string().len() => q,
+            _ => self.clone(),
+        }
+    }
+}
+
+// Queries that might admit exactly the run from min to max, preferred
+// first when equally short. first and last tell whether the run starts at
+// the lowest or ends at the highest published version.
+fn candidates(min: &Version, max: &Version, first: bool, last: bool) -> Vec<VersionQuery> {
+    use VersionQuery::*;
+    let mut queries = vec![];
+    if min == max {
+        queries.push(Version(min.clone()));
+    }
+    if first && last {
+        queries.push(Version(super::Version::SemVer(wildcard(SemVerField::Wildcard, SemVerField::Missing))));
+    }
+    if let super::Version::SemVer(v) = min {
+        queries.push(Compat(min.clone()));
+        queries.push(Approx(min.clone()));
+        let x_major = wildcard(v.major, SemVerField::Wildcard);
+        let x_minor = SemVer { patch: SemVerField::Wildcard, ..wildcard(v.major, v.minor) };
+        queries.push(Version(super::Version::SemVer(x_major)));
+        queries.push(Version(super::Version::SemVer(x_minor)));
+    }
+    if last {
+        queries.push(Gte(min.clone()));
+    }
+    if first {
+        queries.push(Lte(max.clone()));
+    }
+    queries.push(Range(Box::new(Version(min.clone())), Box::new(Version(max.clone()))));
+    queries
+}
+
+// "M.m" with no patch, pre-release or build; "x", i.e. any, for a wildcard major
+fn wildcard(major: SemVerField, minor: SemVerField) -> SemVer {
+    SemVer { major, minor, patch: SemVerField::Missing, pre_release: None, build: None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::npm_semver::parse_range;
+
+    fn simplify(versions: &[&str], range: &str) -> String {
+        let versions: Vec<_> = versions.iter().map(|v| Version::classify(v)).collect();
+        parse_range(range).unwrap().simplify(&versions).to_string()
+    }
+
+    #[test]
+    fn test_simplify() {
+        let versions = ["1.2.2", "1.2.3", "1.5.0", "1.9.0", "1.9.5", "2.0.0"];
+        assert_eq!(simplify(&versions, ">=1.2.3 <2.0.0 || 1.5.x || >=1.9.0 <2.0.0"), "^1.2.3");
+    }
+
+    #[test]
+    fn test_simplify_node_semver() {
+        // node-semver's test/ranges/simplify.js, where ours comes out shorter
+        // the node result is noted
+        let versions = [
+            "1.0.0", "1.0.1", "1.0.2", "1.0.3", "1.0.4", "1.1.0", "1.1.1", "1.1.2",
+            "1.2.0", "1.2.1", "1.2.2", "1.2.3", "1.2.4", "1.2.5", "2.0.0", "2.0.1",
+            "2.1.0", "2.1.1", "2.1.2", "2.2.0", "2.2.1", "2.2.2", "2.3.0", "2.3.1",
+            "2.4.0", "3.0.0", "3.1.0", "3.2.0", "3.3.0",
+        ];
+        assert_eq!(simplify(&versions, "1.x"), "1.x");
+        assert_eq!(simplify(&versions, "1.0.0 || 1.0.1 || 1.0.2 || 1.0.3 || 1.0.4"), "1.0.x"); // <=1.0.4
+        assert_eq!(simplify(&versions, ">=3.0.0 <3.1.0"), "3.0.0");
+        assert_eq!(simplify(&versions, "3.0.0 || 3.1 || 3.2 || 3.3"), "3.x"); // >=3.0.0
+        assert_eq!(simplify(&versions, "1 || 2 || 3"), "x"); // *
+        assert_eq!(simplify(&versions, "2.1 || 2.2 || 2.3"), "2.1.0 - 2.3.1");
+        assert_eq!(simplify(&versions, "1.0.0 || 2.0.0 || 3.0.0"), "1.0.0 || 2.0.0 || 3.0.0");
+    }
+
+    #[test]
+    fn test_simplify_keeps_original() {
+        // nothing published matches, or nothing shorter admits the same
+        assert_eq!(simplify(&["1.0.0"], "^2.0.0"), "^2.0.0");
+        assert_eq!(simplify(&[], "^2.0.0"), "^2.0.0");
+        // a pre-release in the middle of the run is not matched by any candidate
+        assert_eq!(simplify(&["1.0.0", "1.1.0-beta", "1.2.0"], ">=1.0.0 <=1.2.0 || 1.1.0-beta"),
+                   ">=1.0.0 <=1.2.0 || 1.1.0-beta");
+    }
+}
@@ -0,0 +1,226 @@
+// Translating a query between ecosystems whose operators share a spelling
+// but not a meaning:
+//   RubyGems  ~> 1.2   is  >=1.2.0 <2.0.0-0  in npm
+//   Cargo     1.2.3    is  ^1.2.3            in npm
+//   npm       1.2.3    is  =1.2.3            in Cargo
+//
+// The source query is first read the way its ecosystem reads it into npm
+// semantics, which the rest of this crate evaluates, then every node the
+// target would read differently is replaced by its interval form. The
+// result therefore means the same read either way, and render() can write
+// it in the target's syntax.
+use super::interval::{successor, IntervalSet};
+use super::render::RenderError;
+use super::{next_number, Ecosystem, SemVer, SemVerField, Version, VersionQuery};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Translation {
+    pub query: VersionQuery,
+    pub losses: Vec<Loss>, // empty when the translation is exact
+}
+
+// Why a translation is not exact
+#[derive(Debug, Clone, PartialEq)]
+pub enum Loss {
+    Unsupported(Ecosystem, VersionQuery), // no such operator there, read the npm way
+    SoftRequirement(Version),             // Maven's bare "1.0", read as exactly 1.0
+    PreReleases,                          // Maven matches pre-releases the others exclude
+    Inexpressible(RenderError),           // the target has no syntax for the result
+}
+
+impl Translation {
+    pub fn is_exact(&self) -> bool {
+        self.losses.is_empty()
+    }
+}
+
+impl VersionQuery {
+    pub fn translate(&self, from: Ecosystem, to: Ecosystem) -> Translation {
+        let mut losses = vec![];
+        let npm = read(self, from, &mut losses);
+        if (from == Ecosystem::Maven) != (to == Ecosystem::Maven) {
+            losses.push(Loss::PreReleases);
+        }
+        if let Err(e) = npm.render(to) {
+            losses.push(Loss::Inexpressible(e));
+        }
+        Translation { query: write(&npm, to), losses }
+    }
+}
+
+// self, read the way `from` reads it, in npm semantics
+fn read(q: &VersionQuery, from: Ecosystem, losses: &mut Vec<Loss>) -> VersionQuery {
+    use VersionQuery::*;
+    if from == Ecosystem::Npm {
+        return q.clone();
+    }
+    match (from, q) {
+        (_, And(lhs, rhs)) => And(Box::new(read(lhs, from, losses)), Box::new(read(rhs, from, losses))),
+        (_, Or(lhs, rhs)) => Or(Box::new(read(lhs, from, losses)), Box::new(read(rhs, from, losses))),
+        (_, Not(q)) => Not(Box::new(read(q, from, losses))),
+        (_, Range(..)) => unsupported(q, from, losses),
+        // Cargo reads everything like npm but a bare version
+        (Ecosystem::Cargo, Version(v)) => Compat(v.clone()),
+        (Ecosystem::Cargo, _) => q.clone(),
+        (Ecosystem::Pep440, Approx(v)) => pessimistic(v, 2).unwrap_or_else(|| unsupported(q, from, losses)),
+        (Ecosystem::RubyGems, Approx(v)) => pessimistic(v, 1).unwrap_or_else(|| unsupported(q, from, losses)),
+        (_, Approx(_)) | (_, Compat(_)) => unsupported(q, from, losses),
+        (Ecosystem::Maven, Version(v)) => {
+            losses.push(Loss::SoftRequirement(v.clone()));
+            Eq(pad(v))
+        }
+        // the others compare missing numbers as zeros: <=1.2 is <=1.2.0
+        (_, Version(v)) | (_, Eq(v)) => Eq(pad(v)),
+        (_, Lt(v)) => Lt(pad(v)),
+        (_, Lte(v)) => Lte(pad(v)),
+        (_, Gt(v)) => Gt(pad(v)),
+        (_, Gte(v)) => Gte(pad(v)),
+    }
+}
+
+fn unsupported(q: &VersionQuery, from: Ecosystem, losses: &mut Vec<Loss>) -> VersionQuery {
+    losses.push(Loss::Unsupported(from, q.clone()));
+    q.clone()
+}
+
+// npm semantics, written with the nodes `to` reads the same way
+fn write(q: &VersionQuery, to: Ecosystem) -> VersionQuery {
+    use VersionQuery::*;
+    if to == Ecosystem::Npm {
+        return q.clone();
+    }
+    let complete = |v: &super::Version| match v {
+        super::Version::SemVer(v) => v.is_complete(),
+        _ => true, // other schemes compare the same everywhere
+    };
+    match (to, q) {
+        (_, And(lhs, rhs)) => And(Box::new(write(lhs, to)), Box::new(write(rhs, to))),
+        (_, Or(lhs, rhs)) => Or(Box::new(write(lhs, to)), Box::new(write(rhs, to))),
+        (_, Not(q)) => Not(Box::new(write(q, to))),
+        (_, Version(v)) if to == Ecosystem::Cargo || complete(v) => Eq(v.clone()),
+        (Ecosystem::Cargo, Range(..)) => from_intervals(&q.to_intervals()),
+        (Ecosystem::Cargo, _) => q.clone(),
+        (_, Lt(v)) | (_, Lte(v)) | (_, Gt(v)) | (_, Gte(v)) | (_, Eq(v)) if complete(v) => q.clone(),
+        _ => from_intervals(&q.to_intervals()),
+    }
+}
+
+// PEP 440 ~= and RubyGems ~> drop the last number and bump the one before:
+//   ~=1.2 is >=1.2.0 <2.0.0-0, ~=1.2.3 is >=1.2.3 <1.3.0-0
+// RubyGems also takes a single number, ~> 1 is >=1.0.0 <2.0.0-0.
+fn pessimistic(v: &Version, min_numbers: usize) -> Option<VersionQuery> {
+    use VersionQuery::{And, Gte, Lt};
+    let sv = match v {
+        Version::SemVer(sv) => sv,
+        _ => return None,
+    };
+    let fields = [sv.major, sv.minor, sv.patch];
+    let n = fields.iter().take_while(|f| matches!(f, SemVerField::Number(_))).count();
+    if n < min_numbers || fields[n..].iter().any(|f| *f != SemVerField::Missing) {
+        return None;
+    }
+    let lower = Gte(pad(v));
+    let bumped = n.max(2) - 2; // index of the number to bump
+    let mut upper = SemVer::new(0, 0, 0);
+    for (i, field) in fields[..=bumped].iter().enumerate() {
+        let next = match (*field, i == bumped) {
+            (SemVerField::Number(n), true) => next_number(n),
+            (SemVerField::Number(n), false) => Some(n),
+            _ => unreachable!(),
+        };
+        let next = match next {
+            Some(next) => SemVerField::Number(next),
+            None => return Some(lower), // nothing above it
+        };
+        match i {
+            0 => upper.major = next,
+            _ => upper.minor = next,
+        }
+    }
+    upper.pre_release = Some("0".to_string());
+    Some(And(Box::new(lower), Box::new(Lt(Version::SemVer(upper)))))
+}
+
+// missing numbers as zeros, "1.2" => "1.2.0"; wildcards are kept since
+// ==1.2.* means 1.2.x everywhere
+fn pad(v: &Version) -> Version {
+    match v {
+        Version::SemVer(sv) if ![sv.major, sv.minor, sv.patch].contains(&SemVerField::Wildcard) => {
+            let zero = |f: SemVerField| match f {
+                SemVerField::Missing => SemVerField::Number(0),
+                f => f,
+            };
+            Version::SemVer(SemVer {
+                major: zero(sv.major),
+                minor: zero(sv.minor),
+                patch: zero(sv.patch),
+                ..sv.clone()
+            })
+        }
+        v => v.clone(),
+    }
+}
+
+// The query admitting exactly the versions of set, in comparators on
+// complete versions which read the same in every ecosystem
+fn from_intervals(set: &IntervalSet) -> VersionQuery {
+    use VersionQuery::{And, Eq, Gte, Lt, Or};
+    let mut floor = SemVer::new(0, 0, 0);
+    floor.pre_release = Some("0".to_string());
+    let disjuncts = set.intervals().iter().map(|i| match (&i.lower, &i.upper) {
+        (Some(l), Some(u)) if successor(l).as_ref() == Some(u) => Eq(Version::SemVer(l.clone())),
+        (Some(l), Some(u)) => And(
+            Box::new(Gte(Version::SemVer(l.clone()))),
+            Box::new(Lt(Version::SemVer(u.clone()))),
+        ),
+        (Some(l), None) => Gte(Version::SemVer(l.clone())),
+        (None, Some(u)) => Lt(Version::SemVer(u.clone())),
+        (None, None) => Gte(Version::SemVer(floor.clone())),
+    });
+    disjuncts.reduce(|acc, q| Or(Box::new(acc), Box::new(q)))
+        .unwrap_or(Lt(Version::SemVer(floor)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::npm_semver::{parse_partial, parse_range};
+    use Ecosystem::*;
+
+    // The npm parser builds the same AST the other ecosystems' parsers
+    // would for these, e.g. "~1.2" for RubyGems' "~> 1.2".
+    fn translate(range: &str, from: Ecosystem, to: Ecosystem) -> (String, Vec<Loss>) {
+        let t = parse_range(range).unwrap().translate(from, to);
+        (t.query.render(to).unwrap_or_else(|e| e.to_string()), t.losses)
+    }
+
+    #[test]
+    fn test_translate_exact() {
+        assert_eq!(translate("~1.2", Npm, Cargo), ("~1.2".to_string(), vec![]));
+        assert_eq!(translate("^0.0.3", Npm, Cargo), ("^0.0.3".to_string(), vec![]));
+        assert_eq!(translate("1.2.3", Npm, Cargo), ("=1.2.3".to_string(), vec![]));
+        assert_eq!(translate("1.2.3", Cargo, Npm), ("^1.2.3".to_string(), vec![]));
+        assert_eq!(translate("~1.2", RubyGems, Npm), (">=1.2.0 <2.0.0-0".to_string(), vec![]));
+        assert_eq!(translate("~1", RubyGems, Npm), (">=1.0.0 <2.0.0-0".to_string(), vec![]));
+        assert_eq!(translate("~1.2.3", Pep440, RubyGems), (">= 1.2.3, < 1.3.0".to_string(), vec![]));
+        assert_eq!(translate("~1.2", Npm, Pep440), (">=1.2.0, <1.3.0".to_string(), vec![]));
+        assert_eq!(translate("<=1.2", Pep440, Npm), ("<=1.2.0".to_string(), vec![]));
+        assert_eq!(translate("<=1.2", Npm, Pep440), ("<1.3.0".to_string(), vec![]));
+        assert_eq!(translate("1.2.x", Pep440, Cargo), ("1.2.*".to_string(), vec![]));
+    }
+
+    #[test]
+    fn test_translate_lossy() {
+        assert_eq!(translate("^1 || ^3", Npm, Cargo),
+                   ("Cargo ranges cannot express a union of disjoint ranges".to_string(),
+                    vec![Loss::Inexpressible(RenderError::Disjunction(Cargo))]));
+        let (_, losses) = translate("~1", Pep440, Npm);
+        assert_eq!(losses, vec![Loss::Unsupported(Pep440, parse_range("~1").unwrap())]);
+        let (_, losses) = translate("^1.2", RubyGems, Npm);
+        assert_eq!(losses, vec![Loss::Unsupported(RubyGems, parse_range("^1.2").unwrap())]);
+        assert_eq!(translate("1.0", Maven, Npm),
+                   ("=1.0.0".to_string(),
+                    vec![Loss::SoftRequirement(Version::SemVer(parse_partial("1.0").unwrap())), Loss::PreReleases]));
+        assert!(!parse_range("^1.2.3").unwrap().translate(Npm, Maven).is_exact());
+    }
+}